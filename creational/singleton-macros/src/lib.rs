@@ -0,0 +1,169 @@
+// 单例模式过程宏: #[singleton]
+// 在结构体上生成静态存储和访问函数，避免像方案1~5那样手写get_instance
+//
+// 用法:
+//   #[singleton(strategy = "once_lock")]               // 使用Default::default()初始化
+//   #[singleton(strategy = "mutex", init = new_config)] // 使用指定的初始化函数
+//
+// 支持的策略:
+//   once_lock    -> fn get_instance() -> &'static T                (OnceLock，懒汉式)
+//   mutex        -> fn get_instance() -> MutexGuard<'static, T>    (Mutex，可变)
+//                   fn with(f) / fn with_mut(f)                     (闭包访问)
+//   eager        -> fn get_instance() -> &'static T                (饿汉式，init必须是const fn)
+//   thread_local -> fn with(f)                                      (每个线程一个实例)
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{Data, DeriveInput, Error, LitStr, Path, Result, parse_macro_input};
+
+#[derive(Clone, Copy)]
+enum Strategy {
+    OnceLock,
+    Mutex,
+    Eager,
+    ThreadLocal,
+}
+
+const STRATEGIES: &str = r#""once_lock", "mutex", "eager", "thread_local""#;
+
+impl Strategy {
+    fn parse(lit: &LitStr) -> Result<Self> {
+        match lit.value().as_str() {
+            "once_lock" => Ok(Strategy::OnceLock),
+            "mutex" => Ok(Strategy::Mutex),
+            "eager" => Ok(Strategy::Eager),
+            "thread_local" => Ok(Strategy::ThreadLocal),
+            other => Err(Error::new(
+                lit.span(),
+                format!(
+                    "unknown singleton strategy `{}`, expected one of {}",
+                    other, STRATEGIES
+                ),
+            )),
+        }
+    }
+}
+
+// 宏参数
+#[derive(Default)]
+struct Args {
+    strategy: Option<Strategy>,
+    init: Option<Path>,
+}
+
+impl Args {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("strategy") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.strategy = Some(Strategy::parse(&lit)?);
+            Ok(())
+        } else if meta.path.is_ident("init") {
+            // 同时支持 init = new_config 和 init = "new_config"
+            let value = meta.value()?;
+            self.init = Some(if value.peek(LitStr) {
+                value.parse::<LitStr>()?.parse()?
+            } else {
+                value.parse()?
+            });
+            Ok(())
+        } else {
+            Err(meta.error("unsupported singleton argument, expected `strategy` or `init`"))
+        }
+    }
+}
+
+#[proc_macro_attribute]
+pub fn singleton(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed = Args::default();
+    let parser = syn::meta::parser(|meta| parsed.parse(meta));
+    parse_macro_input!(args with parser);
+    let item = parse_macro_input!(input as DeriveInput);
+    expand(parsed, item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(args: Args, item: DeriveInput) -> Result<TokenStream2> {
+    if !matches!(item.data, Data::Struct(_)) {
+        return Err(Error::new(
+            item.ident.span(),
+            "#[singleton] can only be applied to structs",
+        ));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &item.generics,
+            "#[singleton] does not support generic structs, a static cannot depend on generic parameters",
+        ));
+    }
+
+    let ident = &item.ident;
+    let vis = &item.vis;
+    let strategy = args.strategy.unwrap_or(Strategy::OnceLock);
+    let init = match &args.init {
+        Some(path) => quote!(#path()),
+        None => quote!(<#ident as ::core::default::Default>::default()),
+    };
+
+    let accessors = match strategy {
+        Strategy::OnceLock => quote! {
+            #vis fn get_instance() -> &'static #ident {
+                static INSTANCE: ::std::sync::OnceLock<#ident> = ::std::sync::OnceLock::new();
+                INSTANCE.get_or_init(|| #init)
+            }
+        },
+        Strategy::Mutex => quote! {
+            fn __singleton_instance() -> &'static ::std::sync::Mutex<#ident> {
+                static INSTANCE: ::std::sync::OnceLock<::std::sync::Mutex<#ident>> =
+                    ::std::sync::OnceLock::new();
+                INSTANCE.get_or_init(|| ::std::sync::Mutex::new(#init))
+            }
+
+            #vis fn get_instance() -> ::std::sync::MutexGuard<'static, #ident> {
+                Self::__singleton_instance()
+                    .lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner)
+            }
+
+            #vis fn with<R>(f: impl ::core::ops::FnOnce(&#ident) -> R) -> R {
+                f(&Self::get_instance())
+            }
+
+            #vis fn with_mut<R>(f: impl ::core::ops::FnOnce(&mut #ident) -> R) -> R {
+                f(&mut Self::get_instance())
+            }
+        },
+        Strategy::Eager => {
+            // 饿汉式在编译期初始化，不能使用非const的Default::default()
+            if args.init.is_none() {
+                return Err(Error::new(
+                    ident.span(),
+                    "strategy = \"eager\" requires `init = ...` naming a `const fn` initializer",
+                ));
+            }
+            quote! {
+                #vis fn get_instance() -> &'static #ident {
+                    static INSTANCE: #ident = #init;
+                    &INSTANCE
+                }
+            }
+        }
+        Strategy::ThreadLocal => quote! {
+            #vis fn with<R>(f: impl ::core::ops::FnOnce(&#ident) -> R) -> R {
+                ::std::thread_local! {
+                    static INSTANCE: #ident = #init;
+                }
+                INSTANCE.with(f)
+            }
+        },
+    };
+
+    Ok(quote! {
+        #item
+
+        impl #ident {
+            #accessors
+        }
+    })
+}
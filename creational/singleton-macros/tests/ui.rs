@@ -0,0 +1,7 @@
+// #[singleton]误用时的编译错误信息测试
+// 更新期望输出: TRYBUILD=overwrite cargo test -p singleton-macros
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
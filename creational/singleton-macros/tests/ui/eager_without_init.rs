@@ -0,0 +1,9 @@
+use singleton_macros::singleton;
+
+#[singleton(strategy = "eager")]
+#[derive(Default)]
+struct Limits {
+    max: usize,
+}
+
+fn main() {}
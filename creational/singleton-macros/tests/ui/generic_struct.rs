@@ -0,0 +1,9 @@
+use singleton_macros::singleton;
+
+#[singleton(strategy = "once_lock")]
+#[derive(Default)]
+struct Config<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use singleton_macros::singleton;
+
+// 没有指定init时需要实现Default
+#[singleton(strategy = "once_lock")]
+struct Config {
+    name: String,
+}
+
+fn main() {}
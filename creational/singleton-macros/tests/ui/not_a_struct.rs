@@ -0,0 +1,9 @@
+use singleton_macros::singleton;
+
+#[singleton]
+enum Mode {
+    Debug,
+    Release,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use singleton_macros::singleton;
+
+#[singleton(strategy = "mutex", name = "config")]
+#[derive(Default)]
+struct Config;
+
+fn main() {}
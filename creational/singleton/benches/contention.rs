@@ -0,0 +1,107 @@
+// 读取竞争基准测试: 多个线程同时读取单例时，HotSwap与Mutex方案的吞吐量对比
+// 运行: cargo bench --bench contention
+use singleton::{HotSwap, Singleton2};
+use std::hint::black_box;
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 每个线程的读取次数
+const READS_PER_THREAD: usize = 200_000;
+
+#[derive(Clone)]
+struct Config {
+    timeout: u64,
+    endpoint: String,
+}
+
+fn load_config() -> Config {
+    Config {
+        timeout: 30,
+        endpoint: "localhost:8080".to_string(),
+    }
+}
+
+static HOT: HotSwap<Config> = HotSwap::new(load_config);
+static LOCKED: Mutex<Option<Arc<Config>>> = Mutex::new(None);
+
+// 一次读取，返回值用于防止编译器优化掉读取
+type Read = fn() -> usize;
+
+// 多个线程同时执行read，返回总耗时
+fn run(threads: usize, read: Read) -> Duration {
+    let barrier = Arc::new(Barrier::new(threads + 1));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                let mut sum = 0;
+                for _ in 0..READS_PER_THREAD {
+                    sum += read();
+                }
+                black_box(sum)
+            })
+        })
+        .collect();
+    barrier.wait();
+    let start = Instant::now();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    start.elapsed()
+}
+
+// HotSwap: 无锁获取快照
+fn read_hot_swap() -> usize {
+    let config = HOT.load();
+    config.endpoint.len() + config.timeout as usize
+}
+
+// Mutex<Arc<T>>: 加锁克隆快照
+fn read_mutex_arc() -> usize {
+    let config = Arc::clone(
+        LOCKED
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| Arc::new(load_config())),
+    );
+    config.endpoint.len() + config.timeout as usize
+}
+
+// 方案2: 持有全局互斥锁读取
+fn read_singleton2() -> usize {
+    Singleton2::get_instance().get_data().len()
+}
+
+fn main() {
+    let cases: [(&str, Read); 3] = [
+        ("HotSwap::load", read_hot_swap),
+        ("Mutex<Arc<T>>", read_mutex_arc),
+        ("Singleton2 (Mutex)", read_singleton2),
+    ];
+    let max_threads = thread::available_parallelism().map_or(4, |n| n.get());
+    let mut threads = 1;
+    while threads <= max_threads {
+        println!("threads = {}", threads);
+        for (name, read) in cases {
+            // 预热: 完成初始化
+            read();
+            let elapsed = run(threads, read);
+            let reads = (threads * READS_PER_THREAD) as f64;
+            println!(
+                "  {:<20} {:>10.1} ns/read {:>12.0} reads/s",
+                name,
+                elapsed.as_nanos() as f64 / reads * threads as f64,
+                reads / elapsed.as_secs_f64()
+            );
+        }
+        threads *= 2;
+    }
+    // 演示写者: 发布新值后读者看到新配置
+    HOT.update(|old| Config {
+        timeout: old.timeout * 2,
+        ..old.clone()
+    });
+    assert_eq!(HOT.load().timeout, 60);
+}
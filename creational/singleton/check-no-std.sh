@@ -0,0 +1,20 @@
+#!/bin/sh
+# no_std构建检查: 在没有标准库的目标上编译关闭std特性的crate
+# 单元测试总会链接标准库(测试框架需要)，只有在no_std目标上构建才能确认库本身不依赖std
+# 用法: ./check-no-std.sh [目标]，默认thumbv7em-none-eabihf，需要先rustup target add
+set -eu
+
+target="${1:-thumbv7em-none-eabihf}"
+cd "$(dirname "$0")"
+
+# 只依赖core
+cargo build --target "$target" --no-default-features
+# core + alloc
+cargo build --target "$target" --no-default-features --features alloc
+# no_std构建不应引入过程宏等只在std下使用的依赖
+if cargo tree --target "$target" --no-default-features --features alloc -e normal --prefix none \
+    | grep -q '^singleton-macros'; then
+    echo "no_std build depends on singleton-macros" >&2
+    exit 1
+fi
+echo "no_std build for $target ok"
@@ -0,0 +1,172 @@
+// 异步初始化的单例容器: AsyncGlobal<T>
+// 特点: 初始化闭包返回Future，并发调用get().await的任务共享同一次进行中的初始化；
+// 负责初始化的任务被取消(Future被drop)时，其他等待者中的一个会接手重新初始化；
+// 不依赖特定的异步运行时，可以配合任意执行器使用
+use crate::stats::{Instrument, SingletonStats};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::time::Instant;
+
+// 默认的初始化闭包类型: 返回装箱的Future，便于在static中声明
+pub type BoxInit<T> = fn() -> Pin<Box<dyn Future<Output = T> + Send>>;
+
+pub struct AsyncGlobal<T, F = BoxInit<T>> {
+    cell: OnceLock<T>,
+    state: Mutex<State>,
+    init: F,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+}
+
+struct State {
+    // 是否有任务正在执行初始化
+    initializing: bool,
+    // 等待初始化结束的任务
+    waiters: Vec<Waker>,
+}
+
+impl<T, F> AsyncGlobal<T, F> {
+    // 创建单例容器，可用于static声明
+    pub const fn new(init: F) -> Self {
+        Self::build(init, None)
+    }
+
+    // 创建开启统计的单例容器: 记录访问次数、初始化Future的执行时间和等待其他任务初始化的时间，
+    // 被取消的初始化不会被记录
+    pub const fn instrumented(init: F, name: &'static str) -> Self {
+        Self::build(init, Some(Instrument::new(name)))
+    }
+
+    const fn build(init: F, instrument: Option<Instrument>) -> Self {
+        AsyncGlobal {
+            cell: OnceLock::new(),
+            state: Mutex::new(State {
+                initializing: false,
+                waiters: Vec::new(),
+            }),
+            init,
+            instrument,
+        }
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+
+    // 获取已初始化的实例，未初始化时返回None且不会触发初始化
+    pub fn get_if_initialized(&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // 尝试成为负责初始化的任务
+    fn try_claim(&self) -> bool {
+        let mut state = self.lock_state();
+        if state.initializing || self.cell.get().is_some() {
+            return false;
+        }
+        state.initializing = true;
+        true
+    }
+
+    // 结束初始化(成功或被取消)，唤醒所有等待者重新检查状态
+    fn release(&self) {
+        let waiters = {
+            let mut state = self.lock_state();
+            state.initializing = false;
+            std::mem::take(&mut state.waiters)
+        };
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+}
+
+impl<T, F, Fut> AsyncGlobal<T, F>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = T>,
+{
+    // 获取单例实例，首次访问时执行异步初始化
+    pub async fn get(&self) -> &T {
+        if let Some(instrument) = &self.instrument {
+            instrument.record_access();
+        }
+        loop {
+            if let Some(value) = self.cell.get() {
+                return value;
+            }
+            if self.try_claim() {
+                // 本任务被取消时claim随Future一起drop，释放初始化权并唤醒等待者
+                let claim = Claim(self);
+                let timer = self.instrument.as_ref().map(Instrument::start_init);
+                let value = (self.init)().await;
+                if let Some(timer) = timer {
+                    timer.finish();
+                }
+                let value = self.cell.get_or_init(|| value);
+                drop(claim);
+                return value;
+            }
+            // 等待其他任务完成初始化的时间计入等待锁的时间
+            let started = Instant::now();
+            WaitInit(self).await;
+            if let Some(instrument) = &self.instrument {
+                instrument.record_lock_wait(started.elapsed());
+            }
+        }
+    }
+}
+
+// 初始化权，drop时释放
+struct Claim<'a, T, F>(&'a AsyncGlobal<T, F>);
+
+impl<T, F> Drop for Claim<'_, T, F> {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+// 等待进行中的初始化结束 (成功或被取消)
+struct WaitInit<'a, T, F>(&'a AsyncGlobal<T, F>);
+
+impl<T, F> Future for WaitInit<'_, T, F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let global = self.0;
+        let mut state = global.lock_state();
+        if !state.initializing || global.cell.get().is_some() {
+            return Poll::Ready(());
+        }
+        if !state
+            .waiters
+            .iter()
+            .any(|waker| waker.will_wake(cx.waker()))
+        {
+            state.waiters.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for AsyncGlobal<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell.get() {
+            Some(value) => f.debug_tuple("AsyncGlobal").field(value).finish(),
+            None => f.write_str("AsyncGlobal(<uninit>)"),
+        }
+    }
+}
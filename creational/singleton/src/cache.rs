@@ -0,0 +1,430 @@
+// 全局缓存管理器: CacheManager
+// 进程内按名称管理多个类型化的缓存，每个缓存有容量上限(LRU淘汰)、条目过期时间(TTL)、
+// 命中/未命中统计，并支持显式失效；缓存各自加锁，多个线程访问不同缓存时互不阻塞
+use std::any::{Any, type_name};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+// 缓存的配置，使用结构体字面量构造，未指定的字段用..Default::default()补齐
+#[derive(Debug, Clone, Copy)]
+pub struct CacheConfig {
+    // 最多保存的条目数，超出时淘汰最久未使用的条目
+    pub capacity: usize,
+    // 条目的默认存活时间，None或超出时钟范围的值表示不过期
+    pub ttl: Option<Duration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            capacity: 1024,
+            ttl: None,
+        }
+    }
+}
+
+// 缓存的统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    // 因容量上限被淘汰的条目数
+    pub evictions: u64,
+    // 因过期被移除的条目数
+    pub expirations: u64,
+    // 当前条目数 (可能包含尚未被发现的过期条目)
+    pub len: usize,
+    pub capacity: usize,
+}
+
+// 访问缓存管理器失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheError {
+    // 没有该名称的缓存
+    NotFound(String),
+    // 该名称的缓存存在，但键/值类型不同
+    TypeMismatch {
+        name: String,
+        // 请求的Cache<K, V>类型名
+        requested: &'static str,
+    },
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::NotFound(name) => write!(f, "cache `{}` does not exist", name),
+            CacheError::TypeMismatch { name, requested } => write!(
+                f,
+                "cache `{}` exists with different key/value types than {}",
+                name, requested
+            ),
+        }
+    }
+}
+
+impl Error for CacheError {}
+
+struct Entry<V> {
+    value: V,
+    // 最近一次使用的序号，用于LRU
+    tick: u64,
+    expires_at: Option<Instant>,
+}
+
+struct Inner<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    // 使用序号 -> 键，序号最小的是最久未使用的条目
+    recency: BTreeMap<u64, K>,
+    next_tick: u64,
+    stats: CacheStats,
+}
+
+// 单个类型化的缓存
+pub struct Cache<K, V> {
+    config: CacheConfig,
+    inner: Mutex<Inner<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Cache<K, V> {
+    // 创建独立的缓存 (不登记到缓存管理器)
+    pub fn new(config: CacheConfig) -> Self {
+        Cache {
+            config,
+            inner: Mutex::new(Inner {
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                next_tick: 0,
+                stats: CacheStats {
+                    capacity: config.capacity,
+                    ..CacheStats::default()
+                },
+            }),
+        }
+    }
+
+    // 查找条目，命中时刷新其最近使用时间
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut inner = self.lock_inner();
+        let value = inner.touch(key, Instant::now());
+        match value {
+            Some(_) => inner.stats.hits += 1,
+            None => inner.stats.misses += 1,
+        }
+        value
+    }
+
+    // 查找条目，未命中时调用f计算并插入
+    // f在锁外执行，并发未命中时f可能被执行多次，以最后插入的值为准
+    pub fn get_or_insert_with(&self, key: K, f: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = f();
+        self.insert(key, value.clone());
+        value
+    }
+
+    // 插入条目，使用缓存的默认存活时间
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_entry(key, value, self.config.ttl);
+    }
+
+    // 插入条目并指定存活时间
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) {
+        self.insert_entry(key, value, Some(ttl));
+    }
+
+    fn insert_entry(&self, key: K, value: V, ttl: Option<Duration>) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut inner = self.lock_inner();
+        inner.remove(&key);
+        // 先清理过期条目，仍然超出容量时再淘汰最久未使用的条目
+        if inner.entries.len() >= self.config.capacity {
+            inner.purge_expired(now);
+        }
+        while inner.entries.len() >= self.config.capacity {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+            inner.stats.evictions += 1;
+        }
+        let tick = inner.next_tick();
+        inner.recency.insert(tick, key.clone());
+        inner.entries.insert(
+            key,
+            Entry {
+                value,
+                tick,
+                // 超出时钟范围的存活时间(例如Duration::MAX)视为不过期
+                expires_at: ttl.and_then(|ttl| now.checked_add(ttl)),
+            },
+        );
+    }
+
+    // 使条目失效，返回被移除的值
+    pub fn invalidate(&self, key: &K) -> Option<V> {
+        self.lock_inner().remove(key)
+    }
+
+    // 使所有条目失效
+    pub fn invalidate_all(&self) {
+        let mut inner = self.lock_inner();
+        inner.entries.clear();
+        inner.recency.clear();
+    }
+
+    // 移除所有已过期的条目
+    pub fn purge_expired(&self) {
+        self.lock_inner().purge_expired(Instant::now());
+    }
+
+    // 当前条目数
+    pub fn len(&self) -> usize {
+        self.lock_inner().entries.len()
+    }
+
+    // 是否没有任何条目
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 统计快照
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.lock_inner();
+        CacheStats {
+            len: inner.entries.len(),
+            ..inner.stats
+        }
+    }
+
+    fn lock_inner(&self) -> MutexGuard<'_, Inner<K, V>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Inner<K, V> {
+    fn next_tick(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    // 取出未过期条目的值并刷新使用序号，过期条目在此时被移除
+    fn touch(&mut self, key: &K, now: Instant) -> Option<V> {
+        let expired = self
+            .entries
+            .get(key)?
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now);
+        if expired {
+            self.remove(key);
+            self.stats.expirations += 1;
+            return None;
+        }
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key)?;
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let value = entry.value.clone();
+        if let Some(key) = self.recency.remove(&old_tick) {
+            self.recency.insert(tick, key);
+        }
+        Some(value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.tick);
+        Some(entry.value)
+    }
+
+    fn purge_expired(&mut self, now: Instant) {
+        let expired: Vec<K> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expires_at.is_some_and(|at| at <= now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.remove(&key);
+            self.stats.expirations += 1;
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> fmt::Debug for Cache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("config", &self.config)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+// 缓存管理器对具体缓存类型的擦除接口
+trait ManagedCache: Send + Sync {
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+    fn stats(&self) -> CacheStats;
+    fn invalidate_all(&self);
+}
+
+impl<K, V> ManagedCache for Cache<K, V>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn stats(&self) -> CacheStats {
+        Cache::stats(self)
+    }
+
+    fn invalidate_all(&self) {
+        Cache::invalidate_all(self)
+    }
+}
+
+pub struct CacheManager {
+    // 缓存名称 -> 缓存
+    caches: RwLock<BTreeMap<String, Arc<dyn ManagedCache>>>,
+}
+
+// 进程级的全局缓存管理器
+static GLOBAL_CACHES: CacheManager = CacheManager::new();
+
+impl CacheManager {
+    // 创建空的缓存管理器，可用于static声明
+    pub const fn new() -> Self {
+        CacheManager {
+            caches: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    // 获取全局缓存管理器
+    pub fn global() -> &'static CacheManager {
+        &GLOBAL_CACHES
+    }
+
+    // 获取名为name的缓存，不存在时按config创建 (已存在时忽略config)
+    // 同名缓存的键/值类型不同时返回错误
+    pub fn cache<K, V>(
+        &self,
+        name: &str,
+        config: CacheConfig,
+    ) -> Result<Arc<Cache<K, V>>, CacheError>
+    where
+        K: Hash + Eq + Clone + Send + 'static,
+        V: Clone + Send + 'static,
+    {
+        if let Ok(cache) = self.get(name) {
+            return Ok(cache);
+        }
+        let mut caches = self.caches.write().unwrap_or_else(PoisonError::into_inner);
+        let cache = caches
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(Cache::<K, V>::new(config)));
+        downcast(name, Arc::clone(cache))
+    }
+
+    // 获取已存在的名为name的缓存
+    pub fn get<K, V>(&self, name: &str) -> Result<Arc<Cache<K, V>>, CacheError>
+    where
+        K: Hash + Eq + Clone + Send + 'static,
+        V: Clone + Send + 'static,
+    {
+        let cache = self
+            .caches
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| CacheError::NotFound(name.to_string()))?;
+        downcast(name, cache)
+    }
+
+    // 移除名为name的缓存，已获取的Arc<Cache>仍然可用但不再受管理
+    pub fn remove(&self, name: &str) -> bool {
+        self.caches
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name)
+            .is_some()
+    }
+
+    // 使所有缓存的所有条目失效
+    pub fn invalidate_all(&self) {
+        for cache in self.snapshot() {
+            cache.invalidate_all();
+        }
+    }
+
+    // 所有缓存的名称 (按名称排序)
+    pub fn names(&self) -> Vec<String> {
+        self.caches
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    // 所有缓存的统计 (按名称排序)
+    pub fn stats(&self) -> Vec<(String, CacheStats)> {
+        let caches: Vec<_> = self
+            .caches
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(name, cache)| (name.clone(), Arc::clone(cache)))
+            .collect();
+        // 在管理器锁外读取各缓存的统计
+        caches
+            .into_iter()
+            .map(|(name, cache)| (name, cache.stats()))
+            .collect()
+    }
+
+    fn snapshot(&self) -> Vec<Arc<dyn ManagedCache>> {
+        self.caches
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for CacheManager {
+    fn default() -> Self {
+        CacheManager::new()
+    }
+}
+
+impl fmt::Debug for CacheManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.stats()).finish()
+    }
+}
+
+fn downcast<K, V>(name: &str, cache: Arc<dyn ManagedCache>) -> Result<Arc<Cache<K, V>>, CacheError>
+where
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    cache
+        .as_any()
+        .downcast::<Cache<K, V>>()
+        .map_err(|_| CacheError::TypeMismatch {
+            name: name.to_string(),
+            requested: type_name::<Cache<K, V>>(),
+        })
+}
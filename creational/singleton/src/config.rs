@@ -0,0 +1,274 @@
+// 全局配置管理单例: Config
+// 单例模式最典型的用途。启动时调用一次Config::init:
+// 从key=value/类TOML格式的文件加载配置，再用带前缀的环境变量覆盖，并校验必需的配置项，
+// 任何问题都以ConfigError返回而不是panic；之后通过Config::get_instance读取，
+// 类型化的getter负责解析值并支持默认值
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+// 加载配置时的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    // 读取配置文件失败
+    Io {
+        path: String,
+        message: String,
+    },
+    // 配置文件格式错误 (行号从1开始)
+    Parse {
+        line: usize,
+        message: String,
+    },
+    // 缺少必需的配置项
+    Missing(Vec<String>),
+    // 配置值无法解析为请求的类型
+    Invalid {
+        key: String,
+        value: String,
+        expected: &'static str,
+    },
+    // 全局配置已经初始化过
+    AlreadyInitialized,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, message } => {
+                write!(f, "cannot read config file `{}`: {}", path, message)
+            }
+            ConfigError::Parse { line, message } => {
+                write!(f, "config syntax error on line {}: {}", line, message)
+            }
+            ConfigError::Missing(keys) => {
+                write!(f, "missing required config keys: {}", keys.join(", "))
+            }
+            ConfigError::Invalid {
+                key,
+                value,
+                expected,
+            } => write!(
+                f,
+                "config key `{}` has value `{}`, expected {}",
+                key, value, expected
+            ),
+            ConfigError::AlreadyInitialized => f.write_str("global config is already initialized"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+// 配置来源，使用结构体字面量构造，未指定的字段用..Default::default()补齐
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConfigSource<'a> {
+    // 配置文件路径
+    pub file: Option<&'a Path>,
+    // 环境变量前缀，例如"APP_"，APP_DB__URL覆盖db.url
+    pub env_prefix: Option<&'a str>,
+    // 必需的配置项，缺少任何一个时初始化失败
+    pub required: &'a [&'a str],
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    // 配置项，节中的键以"节名.键名"保存
+    values: BTreeMap<String, String>,
+}
+
+// 全局配置
+static GLOBAL_CONFIG: OnceLock<Config> = OnceLock::new();
+// 初始化锁，保证只有一个线程执行加载
+static INIT_LOCK: Mutex<()> = Mutex::new(());
+
+impl Config {
+    // 按配置来源加载并安装全局配置，只能成功调用一次
+    pub fn init(source: &ConfigSource<'_>) -> Result<&'static Config, ConfigError> {
+        let _guard = INIT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        if GLOBAL_CONFIG.get().is_some() {
+            return Err(ConfigError::AlreadyInitialized);
+        }
+        let config = Config::load(source)?;
+        Ok(GLOBAL_CONFIG.get_or_init(|| config))
+    }
+
+    // 获取全局配置，尚未初始化时返回None
+    pub fn get_instance() -> Option<&'static Config> {
+        GLOBAL_CONFIG.get()
+    }
+
+    // 按配置来源加载配置 (不安装为全局配置)
+    pub fn load(source: &ConfigSource<'_>) -> Result<Config, ConfigError> {
+        let mut config = match source.file {
+            Some(path) => {
+                let text = fs::read_to_string(path).map_err(|err| ConfigError::Io {
+                    path: path.display().to_string(),
+                    message: err.to_string(),
+                })?;
+                Config::parse(&text)?
+            }
+            None => Config::default(),
+        };
+        if let Some(prefix) = source.env_prefix {
+            // 忽略名称或值不是UTF-8的环境变量
+            let vars = std::env::vars_os().filter_map(|(key, value)| {
+                Some((key.into_string().ok()?, value.into_string().ok()?))
+            });
+            config.overlay_env(prefix, vars);
+        }
+        config.validate(source.required)?;
+        Ok(config)
+    }
+
+    // 解析配置文本
+    // 支持: key = value、[section]节、#或;开头的注释行、值后面的 # 注释，
+    // 以及带\" \\ \n \t转义的双引号字符串
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut values = BTreeMap::new();
+        let mut section = String::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            let error = |message: &str| ConfigError::Parse {
+                line: index + 1,
+                message: message.to_string(),
+            };
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| error("unterminated section header"))?
+                    .trim();
+                if name.is_empty() {
+                    return Err(error("empty section name"));
+                }
+                section = format!("{}.", name);
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `key = value`"))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(error("empty key"));
+            }
+            let value = parse_value(value.trim()).map_err(error)?;
+            values.insert(format!("{}{}", section, key), value);
+        }
+        Ok(Config { values })
+    }
+
+    // 用带前缀的环境变量覆盖配置: 去掉前缀后转为小写，双下划线表示节的分隔
+    // 例如前缀"APP_"时，APP_PORT覆盖port，APP_DB__MAX_CONN覆盖db.max_conn
+    pub fn overlay_env(&mut self, prefix: &str, vars: impl IntoIterator<Item = (String, String)>) {
+        for (name, value) in vars {
+            if let Some(key) = name.strip_prefix(prefix)
+                && !key.is_empty()
+            {
+                self.values
+                    .insert(key.to_lowercase().replace("__", "."), value);
+            }
+        }
+    }
+
+    // 校验必需的配置项，一次列出所有缺少的配置项
+    pub fn validate(&self, required: &[&str]) -> Result<(), ConfigError> {
+        let missing: Vec<String> = required
+            .iter()
+            .filter(|key| !self.contains(key))
+            .map(|key| key.to_string())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Missing(missing))
+        }
+    }
+
+    // 是否存在配置项
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    // 配置项的原始字符串值
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    // 解析为类型T，配置项不存在时返回Ok(None)
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        self.get_str(key)
+            .map(|value| {
+                value.parse().map_err(|_| ConfigError::Invalid {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    expected: std::any::type_name::<T>(),
+                })
+            })
+            .transpose()
+    }
+
+    // 解析为类型T，配置项不存在时返回默认值 (存在但无法解析时仍返回错误)
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, ConfigError> {
+        Ok(self.get(key)?.unwrap_or(default))
+    }
+
+    // 解析为类型T，配置项不存在时返回ConfigError::Missing
+    pub fn require<T: FromStr>(&self, key: &str) -> Result<T, ConfigError> {
+        self.get(key)?
+            .ok_or_else(|| ConfigError::Missing(vec![key.to_string()]))
+    }
+
+    // 所有配置项的键 (按字典序)
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    // 配置项数量
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    // 是否没有任何配置项
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+// 解析等号右边的值: 双引号字符串或去掉行尾注释的裸值
+fn parse_value(value: &str) -> Result<String, &'static str> {
+    let Some(quoted) = value.strip_prefix('"') else {
+        let bare = match value.find(" #") {
+            Some(comment) => &value[..comment],
+            None => value,
+        };
+        return Ok(bare.trim_end().to_string());
+    };
+    let mut result = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let rest = chars.as_str().trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    return Err("unexpected characters after closing quote");
+                }
+                return Ok(result);
+            }
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(c @ ('"' | '\\')) => result.push(c),
+                _ => return Err("invalid escape sequence"),
+            },
+            c => result.push(c),
+        }
+    }
+    Err("unterminated string")
+}
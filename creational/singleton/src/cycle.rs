@@ -0,0 +1,57 @@
+// 初始化环检测
+// 单例的初始化闭包(直接或经由其他单例)再次访问自己时，OnceLock/Once会死锁或给出含糊的panic信息。
+// 每个线程记录正在初始化的单例栈，进入初始化前检查本单例是否已在栈中，
+// 是则返回列出整条依赖链的错误，例如 "a -> b -> a"
+// 注意: 只能检测同一线程内的环，两个线程互相等待对方初始化的单例仍会死锁
+use crate::lifecycle::AccessError;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+thread_local! {
+    // 当前线程正在初始化的单例: (单例地址, 名称)
+    static INITIALIZING: RefCell<Vec<(usize, &'static str)>> = const { RefCell::new(Vec::new()) };
+}
+
+// 初始化期间持有的守卫，析构时出栈 (包括初始化闭包panic时)
+pub(crate) struct InitGuard {
+    // 是否已入栈 (线程本地存储正在析构时无法入栈)
+    pushed: bool,
+    // 守卫只能在入栈的线程析构
+    _not_send: PhantomData<*const ()>,
+}
+
+// 进入单例的初始化，单例已在当前线程的初始化栈中时返回AccessError::Cycle
+// 单例以地址区分，名称只用于错误信息
+pub(crate) fn enter<K>(key: &K, name: &'static str) -> Result<InitGuard, AccessError> {
+    let key = key as *const K as usize;
+    let entered = INITIALIZING.try_with(|stack| {
+        let mut stack = stack.borrow_mut();
+        if let Some(start) = stack.iter().position(|(entry, _)| *entry == key) {
+            let mut chain: Vec<_> = stack[start..].iter().map(|(_, name)| *name).collect();
+            chain.push(name);
+            return Err(AccessError::Cycle(chain));
+        }
+        stack.push((key, name));
+        Ok(())
+    });
+    let pushed = match entered {
+        Ok(result) => {
+            result?;
+            true
+        }
+        // 线程本地存储正在析构时无法检测，按没有环处理
+        Err(_) => false,
+    };
+    Ok(InitGuard {
+        pushed,
+        _not_send: PhantomData,
+    })
+}
+
+impl Drop for InitGuard {
+    fn drop(&mut self) {
+        if self.pushed {
+            let _ = INITIALIZING.try_with(|stack| stack.borrow_mut().pop());
+        }
+    }
+}
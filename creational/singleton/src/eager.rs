@@ -0,0 +1,110 @@
+// 可一次性配置的饿汉式单例: Eager<T>
+// 特点: 编译期用默认值完成初始化(const)，在第一次读取之前允许调用一次init替换默认值，
+// 之后的init都会返回错误，保证main中的配置过程是确定的
+use crate::stats::{Instrument, SingletonStats};
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+
+pub struct Eager<T> {
+    default: T,
+    // 首次init或首次读取时确定最终取值: Some为init设置的值，None表示使用默认值
+    slot: OnceLock<Option<T>>,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+}
+
+// init失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitError {
+    // 已经调用过init
+    AlreadyInitialized,
+    // 实例已被读取过，不能再修改
+    AlreadyObserved,
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::AlreadyInitialized => f.write_str("singleton is already initialized"),
+            InitError::AlreadyObserved => {
+                f.write_str("singleton was read before init, the default value is in use")
+            }
+        }
+    }
+}
+
+impl Error for InitError {}
+
+impl<T> Eager<T> {
+    // 使用默认值创建单例，可用于static声明
+    pub const fn new(default: T) -> Self {
+        Self::build(default, None)
+    }
+
+    // 创建开启统计的单例: 取值确定(init成功或首次读取锁定默认值)时记录为初始化，并记录读取次数
+    pub const fn instrumented(default: T, name: &'static str) -> Self {
+        Self::build(default, Some(Instrument::new(name)))
+    }
+
+    const fn build(default: T, instrument: Option<Instrument>) -> Self {
+        Eager {
+            default,
+            slot: OnceLock::new(),
+            instrument,
+        }
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 设置单例的值，只能在第一次读取之前调用一次
+    pub fn init(&self, value: T) -> Result<&T, InitError> {
+        let mut value = Some(value);
+        let slot = self.slot.get_or_init(|| self.settle(|| value.take()));
+        match (value, slot) {
+            // 闭包被执行，本次init成功
+            (None, Some(value)) => Ok(value),
+            (Some(_), Some(_)) => Err(InitError::AlreadyInitialized),
+            (_, None) => Err(InitError::AlreadyObserved),
+        }
+    }
+
+    // 获取单例实例，首次读取后默认值被锁定
+    pub fn get(&self) -> &T {
+        if let Some(instrument) = &self.instrument {
+            instrument.record_access();
+        }
+        self.slot
+            .get_or_init(|| self.settle(|| None))
+            .as_ref()
+            .unwrap_or(&self.default)
+    }
+
+    // 确定最终取值，开启统计时记录为初始化
+    fn settle(&self, value: impl FnOnce() -> Option<T>) -> Option<T> {
+        match &self.instrument {
+            Some(instrument) => instrument.record_init(value),
+            None => value(),
+        }
+    }
+
+    // 是否已经通过init设置过值
+    pub fn is_configured(&self) -> bool {
+        matches!(self.slot.get(), Some(Some(_)))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Eager<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.slot.get() {
+            Some(value) => f
+                .debug_tuple("Eager")
+                .field(value.as_ref().unwrap_or(&self.default))
+                .finish(),
+            None => f.debug_tuple("Eager").field(&self.default).finish(),
+        }
+    }
+}
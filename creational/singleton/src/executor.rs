@@ -0,0 +1,34 @@
+// 最小化的执行器: 在当前线程上阻塞运行一个Future
+// 仅用于演示和测试AsyncGlobal，AsyncGlobal本身不依赖任何特定的异步运行时
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+// 被唤醒时unpark阻塞的线程
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// 阻塞当前线程直到Future完成
+pub fn block_on<Fut: Future>(fut: Fut) -> Fut::Output {
+    let mut fut = pin!(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            // park可能被虚假唤醒，重新poll即可
+            Poll::Pending => thread::park(),
+        }
+    }
+}
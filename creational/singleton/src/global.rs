@@ -0,0 +1,262 @@
+// 通用单例容器: Global<T>
+// 特点: 可在const上下文中构造，首次访问时调用用户提供的闭包完成初始化
+// 覆盖方案1~5演示的懒汉式/饿汉式/Once三种策略，任意类型T都可以直接做成全局单例，
+// 使用者无需编写unsafe代码，也无需为每个单例手写static存储
+use crate::cycle;
+use crate::lifecycle::{AccessError, Lifecycle, Teardown};
+use crate::stats::{Instrument, SingletonStats};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Instant;
+
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::OverrideGuard;
+
+pub struct Global<T, F = fn() -> T> {
+    // 已初始化实例的指针，空指针表示尚未初始化
+    ptr: AtomicPtr<T>,
+    // 初始化锁，保证初始化闭包只被一个线程执行
+    lock: Mutex<()>,
+    init: F,
+    // 受生命周期管理时的清理声明
+    managed: Option<Managed<T>>,
+    // 清理回调是否已经执行，初始化并登记清理回调后设置
+    torn_down: OnceLock<Arc<AtomicBool>>,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+    // 测试用的独占锁，保证同一时间只有一个替换守卫
+    #[cfg(any(test, feature = "testing"))]
+    serial: Mutex<()>,
+    _marker: PhantomData<T>,
+}
+
+// 清理声明及登记函数
+// 登记需要T: Send + Sync + 'static，在with_teardown中实例化登记函数，
+// 初始化路径通过函数指针调用，因此get本身不需要这些约束
+struct Managed<T> {
+    teardown: Teardown<T>,
+    // 登记清理回调的生命周期管理器
+    lifecycle: &'static Lifecycle,
+    register: RegisterFn<T>,
+}
+
+type RegisterFn<T> = fn(&Managed<T>, Arc<T>, Arc<AtomicBool>) -> Result<(), AccessError>;
+
+fn register_teardown<T: Send + Sync + 'static>(
+    managed: &Managed<T>,
+    value: Arc<T>,
+    torn_down: Arc<AtomicBool>,
+) -> Result<(), AccessError> {
+    let teardown = &managed.teardown;
+    let hook = teardown.hook;
+    managed
+        .lifecycle
+        .register(teardown.name, teardown.depends_on, move || {
+            // 先标记为已关闭，回调执行期间其他线程的访问也会返回错误
+            torn_down.store(true, Ordering::Release);
+            hook(&value);
+        })
+}
+
+// 实例会在线程间共享(Sync)并可能在其他线程被释放(Send)
+unsafe impl<T: Send + Sync, F: Sync> Sync for Global<T, F> {}
+unsafe impl<T: Send, F: Send> Send for Global<T, F> {}
+
+impl<T, F> Global<T, F> {
+    // 创建单例容器，可用于static声明
+    pub const fn new(init: F) -> Self {
+        Self::build(init, None)
+    }
+
+    // 创建开启统计的单例容器: 记录初始化时间、耗时、线程，访问次数和等待初始化锁的时间
+    pub const fn instrumented(init: F, name: &'static str) -> Self {
+        Self::build(init, Some(Instrument::new(name)))
+    }
+
+    const fn build(init: F, instrument: Option<Instrument>) -> Self {
+        Global {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            lock: Mutex::new(()),
+            init,
+            managed: None,
+            torn_down: OnceLock::new(),
+            instrument,
+            #[cfg(any(test, feature = "testing"))]
+            serial: Mutex::new(()),
+            _marker: PhantomData,
+        }
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        !self.ptr.load(Ordering::Acquire).is_null()
+    }
+
+    // 获取已初始化的实例，未初始化或已关闭时返回None且不会触发初始化
+    pub fn get_if_initialized(&self) -> Option<&T> {
+        self.loaded().ok().flatten()
+    }
+
+    // 是否已经执行过清理回调
+    pub fn is_shut_down(&self) -> bool {
+        self.torn_down
+            .get()
+            .is_some_and(|torn_down| torn_down.load(Ordering::Acquire))
+    }
+
+    fn name(&self) -> &'static str {
+        match (&self.managed, &self.instrument) {
+            (Some(managed), _) => managed.teardown.name,
+            (None, Some(instrument)) => instrument.name(),
+            (None, None) => std::any::type_name::<T>(),
+        }
+    }
+
+    // 读取当前实例: Ok(None)表示尚未初始化
+    fn loaded(&self) -> Result<Option<&T>, AccessError> {
+        if self.is_shut_down() {
+            return Err(AccessError::ShutDown(self.name()));
+        }
+        let p = self.ptr.load(Ordering::Acquire);
+        // 指针只会指向由Arc分配且在self存活期间不会释放的实例
+        Ok(unsafe { p.as_ref() })
+    }
+
+    // 重置为未初始化状态，下次访问会重新执行初始化闭包 (仅用于测试)
+    // 旧实例会被泄漏而不是释放，之前获取的引用仍然有效
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reset(&self) {
+        let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        self.ptr.store(ptr::null_mut(), Ordering::Release);
+    }
+
+    // 临时替换单例实例，守卫析构时恢复原实例 (仅用于测试)
+    // 同一单例的多个替换守卫会依次等待，并行的测试各自看到自己的实例
+    #[cfg(any(test, feature = "testing"))]
+    pub fn override_with(&self, value: T) -> OverrideGuard<'_, T> {
+        let serial = self.serial.lock().unwrap_or_else(PoisonError::into_inner);
+        let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        // 与初始化路径一样由Arc分配，析构时才能用Arc::from_raw释放
+        let replacement = Arc::into_raw(Arc::new(value)).cast_mut();
+        OverrideGuard::new(&self.ptr, replacement, serial)
+    }
+}
+
+impl<T: Send + Sync + 'static, F> Global<T, F> {
+    // 创建受生命周期管理的单例: 初始化时登记清理回调，shutdown()时执行，
+    // 之后get会panic，try_get返回AccessError::ShutDown
+    pub const fn with_teardown(init: F, teardown: Teardown<T>) -> Self {
+        Self::with_teardown_in(init, teardown, Lifecycle::global())
+    }
+
+    // 与with_teardown相同，但登记在指定的生命周期管理器中，由lifecycle.shutdown()清理
+    pub const fn with_teardown_in(
+        init: F,
+        teardown: Teardown<T>,
+        lifecycle: &'static Lifecycle,
+    ) -> Self {
+        let mut global = Self::new(init);
+        global.managed = Some(Managed {
+            teardown,
+            lifecycle,
+            register: register_teardown::<T>,
+        });
+        global
+    }
+}
+
+impl<T, F: Fn() -> T> Global<T, F> {
+    // 获取单例实例 (懒汉式: 首次访问时初始化)
+    // 单例已关闭或初始化存在环时panic，需要处理这些场景请使用try_get
+    pub fn get(&self) -> &T {
+        match self.try_get() {
+            Ok(value) => value,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // 获取单例实例，单例已关闭或初始化存在环时返回错误
+    pub fn try_get(&self) -> Result<&T, AccessError> {
+        if let Some(instrument) = &self.instrument {
+            instrument.record_access();
+        }
+        match self.loaded()? {
+            Some(value) => Ok(value),
+            None => self.init_slow(),
+        }
+    }
+
+    // 立即初始化 (饿汉式: 在main开始处调用，之后的访问不再有初始化开销)
+    pub fn force(&self) -> &T {
+        self.get()
+    }
+
+    #[cold]
+    fn init_slow(&self) -> Result<&T, AccessError> {
+        // 初始化闭包再次访问本单例时返回错误，而不是在初始化锁上死锁
+        let _init = cycle::enter(self, self.name())?;
+        // 初始化闭包panic时锁会中毒，这里忽略中毒状态，允许后续调用重试
+        let started = Instant::now();
+        let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(instrument) = &self.instrument {
+            instrument.record_lock_wait(started.elapsed());
+        }
+        // 双重检查: 等待锁期间可能已被其他线程初始化
+        if let Some(value) = self.loaded()? {
+            return Ok(value);
+        }
+        let value = Arc::new(match &self.instrument {
+            Some(instrument) => instrument.record_init(&self.init),
+            None => (self.init)(),
+        });
+        if let Some(managed) = &self.managed {
+            // 关闭之后不再创建新的受管理实例，并标记为已关闭，
+            // 之后的访问直接返回错误而不是反复执行初始化闭包
+            let torn_down = Arc::clone(self.torn_down.get_or_init(Default::default));
+            if let Err(err) =
+                (managed.register)(managed, Arc::clone(&value), Arc::clone(&torn_down))
+            {
+                torn_down.store(true, Ordering::Release);
+                return Err(err);
+            }
+        }
+        let p = Arc::into_raw(value).cast_mut();
+        self.ptr.store(p, Ordering::Release);
+        Ok(unsafe { &*p })
+    }
+}
+
+impl<T, F: Fn() -> T> Deref for Global<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Global<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_if_initialized() {
+            Some(value) => f.debug_tuple("Global").field(value).finish(),
+            None => f.write_str("Global(<uninit>)"),
+        }
+    }
+}
+
+impl<T, F> Drop for Global<T, F> {
+    fn drop(&mut self) {
+        let p = *self.ptr.get_mut();
+        if !p.is_null() {
+            drop(unsafe { Arc::from_raw(p) });
+        }
+    }
+}
@@ -0,0 +1,205 @@
+// 可变的通用单例容器: GlobalMutex<T>
+// 方案1/2的泛型版本(两者都是它的包装): 可以保存任意类型T，
+// update在一次加锁内完成读-改-写，多个线程同时自增计数器也不会丢失更新；
+// 锁中毒时按可配置的策略处理 (默认为PoisonPolicy::default()，即Recover: 继续使用panic时留下的数据)
+use crate::cycle;
+use crate::lifecycle::AccessError;
+use crate::poison::{AtomicPoisonPolicy, PoisonPolicy, PoisonedError};
+use crate::stats::{Instrument, SingletonStats};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+#[cfg(any(test, feature = "testing"))]
+use std::sync::PoisonError;
+
+pub struct GlobalMutex<T, F = fn() -> T> {
+    cell: OnceLock<Mutex<T>>,
+    init: F,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+    // 锁中毒时的处理策略
+    policy: AtomicPoisonPolicy,
+}
+
+// 加锁失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockError {
+    // 锁已中毒且策略为Propagate
+    Poisoned(PoisonedError),
+    // 初始化闭包(直接或经由其他单例)再次访问了本单例
+    Access(AccessError),
+}
+
+impl From<PoisonedError> for LockError {
+    fn from(err: PoisonedError) -> Self {
+        LockError::Poisoned(err)
+    }
+}
+
+impl From<AccessError> for LockError {
+    fn from(err: AccessError) -> Self {
+        LockError::Access(err)
+    }
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::Poisoned(err) => write!(f, "{}", err),
+            LockError::Access(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for LockError {}
+
+impl<T, F> GlobalMutex<T, F> {
+    // 创建单例容器，可用于static声明，首次访问时调用init
+    pub const fn new(init: F) -> Self {
+        Self::build(init, None)
+    }
+
+    // 创建开启统计的单例容器: 记录初始化信息、加锁次数和等待锁的时间
+    pub const fn instrumented(init: F, name: &'static str) -> Self {
+        Self::build(init, Some(Instrument::new(name)))
+    }
+
+    const fn build(init: F, instrument: Option<Instrument>) -> Self {
+        GlobalMutex {
+            cell: OnceLock::new(),
+            init,
+            instrument,
+            policy: AtomicPoisonPolicy::new(PoisonPolicy::DEFAULT),
+        }
+    }
+
+    // 指定锁中毒时的初始处理策略，可用于static声明
+    pub const fn with_poison_policy(mut self, policy: PoisonPolicy) -> Self {
+        self.policy = AtomicPoisonPolicy::new(policy);
+        self
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+
+    // 设置锁中毒时的处理策略
+    pub fn set_poison_policy(&self, policy: PoisonPolicy) {
+        self.policy.store(policy);
+    }
+
+    // 获取锁中毒时的处理策略
+    pub fn poison_policy(&self) -> PoisonPolicy {
+        self.policy.load()
+    }
+
+    fn name(&self) -> &'static str {
+        match &self.instrument {
+            Some(instrument) => instrument.name(),
+            None => std::any::type_name::<T>(),
+        }
+    }
+}
+
+impl<T, F: Fn() -> T> GlobalMutex<T, F> {
+    // 加锁访问实例，首次访问时初始化
+    // 锁中毒且策略为Propagate、或初始化存在环时panic，需要处理这些场景请使用try_lock
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.try_lock().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // 加锁访问实例，按中毒策略处理锁中毒
+    // 初始化过程中再次访问本单例时返回LockError::Access(AccessError::Cycle)，而不是死锁
+    pub fn try_lock(&self) -> Result<MutexGuard<'_, T>, LockError> {
+        let mutex = self.mutex()?;
+        let lock = || mutex.lock();
+        let result = match &self.instrument {
+            Some(instrument) => instrument.acquire(lock),
+            None => lock(),
+        };
+        let poisoned = match result {
+            Ok(guard) => return Ok(guard),
+            Err(poisoned) => poisoned,
+        };
+        match self.policy.load() {
+            PoisonPolicy::Propagate => Err(PoisonedError { name: self.name() }.into()),
+            PoisonPolicy::Recover => {
+                mutex.clear_poison();
+                Ok(poisoned.into_inner())
+            }
+            PoisonPolicy::Reinitialize => {
+                let mut guard = poisoned.into_inner();
+                *guard = (self.init)();
+                mutex.clear_poison();
+                Ok(guard)
+            }
+        }
+    }
+
+    // 忽略中毒状态加锁，测试辅助函数使用
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn lock_ignoring_poison(&self) -> MutexGuard<'_, T> {
+        self.mutex()
+            .unwrap_or_else(|err| panic!("{}", err))
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // 首次访问时初始化，初始化时间不计入等待锁的时间
+    fn mutex(&self) -> Result<&Mutex<T>, AccessError> {
+        if let Some(mutex) = self.cell.get() {
+            return Ok(mutex);
+        }
+        // 初始化闭包再次访问本单例时返回错误，而不是在OnceLock上死锁
+        let _init = cycle::enter(self, self.name())?;
+        Ok(self.cell.get_or_init(|| {
+            Mutex::new(match &self.instrument {
+                Some(instrument) => instrument.record_init(&self.init),
+                None => (self.init)(),
+            })
+        }))
+    }
+
+    // 原子地读-改-写，返回闭包的结果
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    // 替换为新值，返回旧值
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.lock(), value)
+    }
+
+    // 取出当前值并留下默认值
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        std::mem::take(&mut *self.lock())
+    }
+
+    // 当前值的副本
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock().clone()
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for GlobalMutex<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell.get().map(Mutex::try_lock) {
+            Some(Ok(value)) => f.debug_tuple("GlobalMutex").field(&*value).finish(),
+            Some(Err(_)) => f.write_str("GlobalMutex(<locked>)"),
+            None => f.write_str("GlobalMutex(<uninit>)"),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+// 读写锁保护的通用单例容器: GlobalRwLock<T>
+// 方案5的泛型版本(方案5是它的包装): 读多写少的可变单例，多个读者可以同时持有读锁；
+// 除了首次访问时调用构造闭包，还可以按调用传入可失败的初始化闭包(失败后保持未初始化，可重试)，
+// 并可在初始化时向生命周期管理器登记清理回调，shutdown()之后的访问返回错误
+use crate::lifecycle::{AccessError, Lifecycle, Teardown};
+use crate::stats::{Instrument, SingletonStats};
+use crate::try_global::{TryInit, TryInitError};
+use std::convert::Infallible;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub struct GlobalRwLock<T, F = fn() -> T> {
+    // 实例与清理回调共享，清理回调执行时可以读取实例
+    cell: OnceLock<Arc<RwLock<T>>>,
+    init: F,
+    // 初始化锁，同时记录最近一次初始化失败的错误信息
+    // 注意: 失败时不能写入cell，否则无法重试
+    gate: TryInit,
+    // 受生命周期管理时的清理声明
+    managed: Option<Managed<T>>,
+    // 清理回调是否已经执行，初始化并登记清理回调后设置
+    torn_down: OnceLock<Arc<AtomicBool>>,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+}
+
+// 清理声明及登记函数，与Global相同: 登记需要T: Send + Sync + 'static，
+// 在with_teardown_in中实例化登记函数，初始化路径通过函数指针调用
+struct Managed<T> {
+    teardown: Teardown<T>,
+    lifecycle: &'static Lifecycle,
+    register: RegisterFn<T>,
+}
+
+type RegisterFn<T> = fn(&Managed<T>, Arc<RwLock<T>>, Arc<AtomicBool>) -> Result<(), AccessError>;
+
+fn register_teardown<T: Send + Sync + 'static>(
+    managed: &Managed<T>,
+    value: Arc<RwLock<T>>,
+    torn_down: Arc<AtomicBool>,
+) -> Result<(), AccessError> {
+    let teardown = &managed.teardown;
+    let hook = teardown.hook;
+    managed
+        .lifecycle
+        .register(teardown.name, teardown.depends_on, move || {
+            // 先标记为已关闭，回调执行期间其他线程的访问也会返回错误
+            torn_down.store(true, Ordering::Release);
+            hook(&value.read().unwrap_or_else(PoisonError::into_inner));
+        })
+}
+
+impl<T, F> GlobalRwLock<T, F> {
+    // 创建单例容器，可用于static声明
+    pub const fn new(init: F) -> Self {
+        Self::build(init, None)
+    }
+
+    // 创建开启统计的单例容器: 记录初始化和每次加锁(读锁/写锁)的等待时间
+    pub const fn instrumented(init: F, name: &'static str) -> Self {
+        Self::build(init, Some(Instrument::new(name)))
+    }
+
+    const fn build(init: F, instrument: Option<Instrument>) -> Self {
+        GlobalRwLock {
+            cell: OnceLock::new(),
+            init,
+            gate: TryInit::new(),
+            managed: None,
+            torn_down: OnceLock::new(),
+            instrument,
+        }
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+
+    // 是否已经执行过清理回调
+    pub fn is_shut_down(&self) -> bool {
+        self.torn_down
+            .get()
+            .is_some_and(|torn_down| torn_down.load(Ordering::Acquire))
+    }
+
+    // 最近一次初始化失败的错误信息 (初始化成功后仍保留)
+    pub fn last_error(&self) -> Option<String> {
+        self.gate.last_error()
+    }
+
+    // 按调用传入初始化闭包获取实例 (可失败初始化)
+    // 初始化闭包返回错误时实例保持未初始化，之后的调用可以重试；已初始化时不调用闭包
+    // 初始化闭包中再次访问本单例、或单例已关闭时返回TryInitError::Access
+    pub fn get_or_try_init<E: Display>(
+        &self,
+        init: impl FnOnce() -> Result<T, E>,
+    ) -> Result<&RwLock<T>, TryInitError<E>> {
+        let lock = self.gate.run(
+            self.name(),
+            || self.cell.get().map(|lock| &**lock),
+            || {
+                let timer = self.instrument.as_ref().map(Instrument::start_init);
+                let value = init()?;
+                if let Some(timer) = timer {
+                    timer.finish();
+                }
+                Ok(value)
+            },
+            |value| self.publish(value),
+        )?;
+        Ok(self.checked(lock)?)
+    }
+
+    fn name(&self) -> &'static str {
+        match (&self.managed, &self.instrument) {
+            (Some(managed), _) => managed.teardown.name,
+            (None, Some(instrument)) => instrument.name(),
+            (None, None) => std::any::type_name::<T>(),
+        }
+    }
+
+    // 保存初始化结果并登记清理回调，与其他初始化路径并发时以先完成者为准
+    fn publish(&self, value: T) -> &RwLock<T> {
+        self.cell.get_or_init(|| {
+            let value = Arc::new(RwLock::new(value));
+            if let Some(managed) = &self.managed {
+                let torn_down = Arc::clone(self.torn_down.get_or_init(Default::default));
+                // 已经关闭时登记失败，关闭之后才初始化的实例同样不能再被访问
+                if (managed.register)(managed, Arc::clone(&value), Arc::clone(&torn_down)).is_err()
+                {
+                    torn_down.store(true, Ordering::Release);
+                }
+            }
+            value
+        })
+    }
+
+    // 在初始化之后检查，关闭之后才初始化的实例同样不能访问
+    fn checked<'a>(&self, lock: &'a RwLock<T>) -> Result<&'a RwLock<T>, AccessError> {
+        if self.is_shut_down() {
+            return Err(AccessError::ShutDown(self.name()));
+        }
+        Ok(lock)
+    }
+}
+
+impl<T: Send + Sync + 'static, F> GlobalRwLock<T, F> {
+    // 受生命周期管理: 初始化时登记清理回调，shutdown()时执行，
+    // 之后get会panic，try_get返回AccessError::ShutDown；可用于static声明
+    pub const fn with_teardown(self, teardown: Teardown<T>) -> Self {
+        self.with_teardown_in(teardown, Lifecycle::global())
+    }
+
+    // 与with_teardown相同，但登记在指定的生命周期管理器中，由lifecycle.shutdown()清理
+    pub const fn with_teardown_in(
+        mut self,
+        teardown: Teardown<T>,
+        lifecycle: &'static Lifecycle,
+    ) -> Self {
+        self.managed = Some(Managed {
+            teardown,
+            lifecycle,
+            register: register_teardown::<T>,
+        });
+        self
+    }
+}
+
+impl<T, F: Fn() -> T> GlobalRwLock<T, F> {
+    // 获取单例实例，首次访问时初始化
+    // 单例已关闭或初始化存在环时panic，需要处理这些场景请使用try_get
+    pub fn get(&self) -> &RwLock<T> {
+        self.try_get().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // 获取单例实例，shutdown()之后返回AccessError::ShutDown，
+    // 初始化过程中再次访问本单例时返回AccessError::Cycle (而不是死锁)
+    pub fn try_get(&self) -> Result<&RwLock<T>, AccessError> {
+        let lock = match self.cell.get() {
+            Some(lock) => lock,
+            // 与get_or_try_init共用初始化锁，并发的首次访问只执行一次初始化闭包
+            None => self
+                .gate
+                .run(
+                    self.name(),
+                    || self.cell.get().map(|lock| &**lock),
+                    || {
+                        Ok::<_, Infallible>(match &self.instrument {
+                            Some(instrument) => instrument.record_init(&self.init),
+                            None => (self.init)(),
+                        })
+                    },
+                    |value| self.publish(value),
+                )
+                .map_err(|err| match err {
+                    TryInitError::Init(never) => match never {},
+                    TryInitError::Access(err) => err,
+                })?,
+        };
+        self.checked(lock)
+    }
+
+    // 获取读锁，锁中毒时继续使用panic时留下的数据
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        let lock = self.get();
+        let read = || lock.read().unwrap_or_else(PoisonError::into_inner);
+        match &self.instrument {
+            Some(instrument) => instrument.acquire(read),
+            None => read(),
+        }
+    }
+
+    // 获取写锁，锁中毒时继续使用panic时留下的数据
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        let lock = self.get();
+        let write = || lock.write().unwrap_or_else(PoisonError::into_inner);
+        match &self.instrument {
+            Some(instrument) => instrument.acquire(write),
+            None => write(),
+        }
+    }
+
+    // 原子地读-改-写，返回闭包的结果
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.write())
+    }
+
+    // 替换为新值，返回旧值
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.write(), value)
+    }
+
+    // 取出当前值并留下默认值
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        std::mem::take(&mut *self.write())
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for GlobalRwLock<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell.get().map(|lock| lock.try_read()) {
+            Some(Ok(value)) => f.debug_tuple("GlobalRwLock").field(&*value).finish(),
+            Some(Err(_)) => f.write_str("GlobalRwLock(<locked>)"),
+            None => f.write_str("GlobalRwLock(<uninit>)"),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// 读多写少、可热替换的单例: HotSwap<T>
+// 特点: 读者获取Arc<T>快照时不加锁，写者原子地发布新值，旧快照在被drop之前一直有效
+// 适合每个请求都要读取、但一天只替换几次的配置 (对比方案2: 全局Mutex让所有读者串行)
+//
+// 实现: 每次发布分配一个全局唯一的版本号，读者在线程本地缓存最近一次看到的(版本号, 快照的弱引用)，
+// 版本号未变化时直接从弱引用得到Arc；只有版本号变化后的第一次读取才需要短暂加锁刷新缓存。
+// 缓存只持有弱引用，被替换的值在最后一个快照drop时立即释放，不会被空闲线程的缓存拖住
+use crate::stats::{Instrument, SingletonStats};
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::Instant;
+
+pub struct HotSwap<T, F = fn() -> T> {
+    // 当前值，None表示尚未初始化；写者持有此锁发布新值
+    current: Mutex<Option<(u64, Arc<T>)>>,
+    // 当前值的版本号，0表示尚未初始化
+    generation: AtomicU64,
+    init: F,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+}
+
+// 全局递增的版本号，不同HotSwap实例的版本号也不会重复，
+// 因此线程本地缓存不会把一个实例的旧快照误当作另一个实例的当前值
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+type CacheEntry = (usize, u64, Weak<dyn Any + Send + Sync>);
+
+thread_local! {
+    // 当前线程缓存的快照: (HotSwap地址, 版本号, 快照的弱引用)
+    static CACHE: RefCell<Vec<CacheEntry>> = const { RefCell::new(Vec::new()) };
+}
+
+impl<T, F> HotSwap<T, F> {
+    // 创建单例容器，可用于static声明，首次读取时调用init创建初始值
+    pub const fn new(init: F) -> Self {
+        Self::build(init, None)
+    }
+
+    // 创建开启统计的单例容器: 记录读写次数、初始值的创建，以及刷新缓存和写者等待锁的时间
+    // (store/swap发布第一个值时同样记录为初始化)
+    pub const fn instrumented(init: F, name: &'static str) -> Self {
+        Self::build(init, Some(Instrument::new(name)))
+    }
+
+    const fn build(init: F, instrument: Option<Instrument>) -> Self {
+        HotSwap {
+            current: Mutex::new(None),
+            generation: AtomicU64::new(0),
+            init,
+            instrument,
+        }
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.generation.load(Ordering::Acquire) != 0
+    }
+
+    // 加锁，开启统计时记录等待锁的时间 (访问次数由load/swap/update记录)
+    fn lock_current(&self) -> MutexGuard<'_, Option<(u64, Arc<T>)>> {
+        let lock = || self.current.lock().unwrap_or_else(PoisonError::into_inner);
+        match &self.instrument {
+            Some(instrument) => {
+                let started = Instant::now();
+                let current = lock();
+                instrument.record_lock_wait(started.elapsed());
+                current
+            }
+            None => lock(),
+        }
+    }
+
+    fn record_access(&self) {
+        if let Some(instrument) = &self.instrument {
+            instrument.record_access();
+        }
+    }
+
+    // 创建初始值，开启统计时记录为初始化
+    fn create(&self, init: impl FnOnce() -> T) -> Arc<T> {
+        Arc::new(match &self.instrument {
+            Some(instrument) => instrument.record_init(init),
+            None => init(),
+        })
+    }
+
+    fn key(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+impl<T, F> HotSwap<T, F>
+where
+    T: Send + Sync + 'static,
+    F: Fn() -> T,
+{
+    // 获取当前值的快照 (不加锁)
+    pub fn load(&self) -> Arc<T> {
+        self.record_access();
+        let generation = self.generation.load(Ordering::Acquire);
+        if generation != 0 {
+            let cached = CACHE.try_with(|cache| {
+                let cache = cache.try_borrow().ok()?;
+                cache
+                    .iter()
+                    .find(|(key, cached, _)| *key == self.key() && *cached == generation)
+                    .and_then(|(_, _, snapshot)| snapshot.upgrade())
+            });
+            if let Ok(Some(snapshot)) = cached {
+                // 版本号全局唯一，版本号相同的快照一定属于本实例
+                return snapshot.downcast::<T>().expect("snapshot type mismatch");
+            }
+        }
+        self.load_slow()
+    }
+
+    #[cold]
+    fn load_slow(&self) -> Arc<T> {
+        let (generation, snapshot) = {
+            let mut current = self.lock_current();
+            let (generation, snapshot) =
+                current.get_or_insert_with(|| self.publish_locked(self.create(&self.init)));
+            (*generation, Arc::clone(snapshot))
+        };
+        let _ = CACHE.try_with(|cache| {
+            // 读者在回调中嵌套读取时缓存正被借用，此时跳过缓存
+            if let Ok(mut cache) = cache.try_borrow_mut() {
+                // 顺便清理已释放的快照 (被替换的值或已经drop的HotSwap)
+                cache.retain(|(key, _, cached)| *key != self.key() && cached.strong_count() > 0);
+                let snapshot = Arc::clone(&snapshot) as Arc<dyn Any + Send + Sync>;
+                cache.push((self.key(), generation, Arc::downgrade(&snapshot)));
+            }
+        });
+        snapshot
+    }
+
+    // 发布新值，已发出的旧快照不受影响
+    pub fn store(&self, value: T) {
+        self.swap(value);
+    }
+
+    // 发布新值并返回旧值的快照，尚未初始化时返回None (不会为此调用init)
+    pub fn swap(&self, value: T) -> Option<Arc<T>> {
+        self.record_access();
+        let mut current = self.lock_current();
+        let value = match *current {
+            Some(_) => Arc::new(value),
+            None => self.create(|| value),
+        };
+        let new = self.publish_locked(value);
+        current.replace(new).map(|(_, old)| old)
+    }
+
+    // 基于当前值计算并发布新值，写者之间串行执行，不会丢失更新
+    // f返回之前当前值保持不变，f panic时当前值不受影响
+    pub fn update(&self, f: impl FnOnce(&T) -> T) -> Arc<T> {
+        self.record_access();
+        let mut current = self.lock_current();
+        let old = match &*current {
+            Some((_, old)) => Arc::clone(old),
+            None => {
+                let (generation, initial) = self.publish_locked(self.create(&self.init));
+                *current = Some((generation, Arc::clone(&initial)));
+                initial
+            }
+        };
+        let (generation, new) = self.publish_locked(Arc::new(f(&old)));
+        *current = Some((generation, Arc::clone(&new)));
+        new
+    }
+
+    // 分配版本号并公开，调用方必须持有current锁
+    fn publish_locked(&self, value: Arc<T>) -> (u64, Arc<T>) {
+        let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+        self.generation.store(generation, Ordering::Release);
+        (generation, value)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for HotSwap<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &*self.lock_current() {
+            Some((_, value)) => f.debug_tuple("HotSwap").field(value).finish(),
+            None => f.write_str("HotSwap(<uninit>)"),
+        }
+    }
+}
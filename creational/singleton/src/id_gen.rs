@@ -0,0 +1,223 @@
+// 全局唯一ID生成器: Sequence和Snowflake
+// Sequence: 单调递增的原子序列，可直接声明为static (订单号、请求号等进程内计数)
+// Snowflake: 64位分布式ID = 41位毫秒时间戳 | 10位节点号 | 12位毫秒内序号，
+// 启动时通过Snowflake::init配置一次节点号，之后无锁地生成趋势递增且全局唯一的ID
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 单调递增的原子序列
+pub struct Sequence {
+    next: AtomicU64,
+}
+
+impl Sequence {
+    // 从start开始的序列，可用于static声明
+    pub const fn new(start: u64) -> Self {
+        Sequence {
+            next: AtomicU64::new(start),
+        }
+    }
+
+    // 取下一个值，多个线程并发调用时每个值只会被取到一次
+    pub fn next(&self) -> u64 {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+
+    // 下一次next将返回的值
+    pub fn peek(&self) -> u64 {
+        self.next.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sequence")
+            .field("next", &self.peek())
+            .finish()
+    }
+}
+
+const NODE_BITS: u32 = 10;
+const SEQUENCE_BITS: u32 = 12;
+const TIMESTAMP_BITS: u32 = 41;
+const MAX_NODE_ID: u16 = (1 << NODE_BITS) - 1;
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+const TIMESTAMP_MASK: u64 = (1 << TIMESTAMP_BITS) - 1;
+
+// 生成ID失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdError {
+    // 节点号超出10位
+    InvalidNodeId(u16),
+    // 系统时钟回拨超过了容忍范围
+    ClockMovedBackwards(Duration),
+    // 全局ID生成器已经配置过
+    AlreadyInitialized,
+}
+
+impl fmt::Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdError::InvalidNodeId(node_id) => {
+                write!(f, "node id {} exceeds the maximum {}", node_id, MAX_NODE_ID)
+            }
+            IdError::ClockMovedBackwards(by) => {
+                write!(f, "system clock moved backwards by {:?}", by)
+            }
+            IdError::AlreadyInitialized => {
+                f.write_str("global id generator is already initialized")
+            }
+        }
+    }
+}
+
+impl Error for IdError {}
+
+// Snowflake生成器的配置，使用结构体字面量构造，未指定的字段用..Default::default()补齐
+#[derive(Debug, Clone, Copy)]
+pub struct SnowflakeConfig {
+    // 节点号 (0~1023)，同一时刻运行的每个进程必须不同
+    pub node_id: u16,
+    // 时间戳的起点 (Unix毫秒)，41位时间戳可使用约69年
+    pub epoch_ms: u64,
+    // 可容忍的时钟回拨: 回拨不超过此值时沿用上次的时间戳继续生成，超过时返回错误；
+    // 毫秒内序号用尽时也最多向未来借用这么多毫秒
+    pub max_clock_skew: Duration,
+    // 当前时间 (Unix毫秒)，测试时可以替换为可控的时钟
+    pub clock: fn() -> u64,
+}
+
+impl Default for SnowflakeConfig {
+    fn default() -> Self {
+        SnowflakeConfig {
+            node_id: 0,
+            // 2020-01-01T00:00:00Z
+            epoch_ms: 1_577_836_800_000,
+            max_clock_skew: Duration::from_millis(10),
+            clock: system_clock_ms,
+        }
+    }
+}
+
+// 系统时钟的当前时间 (Unix毫秒)
+pub fn system_clock_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_millis() as u64)
+}
+
+// ID的各个组成部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnowflakeParts {
+    // 生成时的时间戳 (Unix毫秒)
+    pub timestamp_ms: u64,
+    pub node_id: u16,
+    pub sequence: u16,
+}
+
+pub struct Snowflake {
+    config: SnowflakeConfig,
+    // 上次生成的 (相对时间戳 << 12 | 序号)，通过CAS无锁更新
+    state: AtomicU64,
+}
+
+// 进程级的ID生成器
+static GLOBAL_SNOWFLAKE: OnceLock<Snowflake> = OnceLock::new();
+
+impl Snowflake {
+    // 创建独立的生成器 (不安装为全局单例)
+    pub fn new(config: SnowflakeConfig) -> Result<Snowflake, IdError> {
+        if config.node_id > MAX_NODE_ID {
+            return Err(IdError::InvalidNodeId(config.node_id));
+        }
+        Ok(Snowflake {
+            config,
+            state: AtomicU64::new(0),
+        })
+    }
+
+    // 按配置创建并安装全局生成器，只能成功调用一次
+    pub fn init(config: SnowflakeConfig) -> Result<&'static Snowflake, IdError> {
+        let mut generator = Some(Snowflake::new(config)?);
+        let installed = GLOBAL_SNOWFLAKE.get_or_init(|| generator.take().unwrap());
+        match generator {
+            None => Ok(installed),
+            Some(_) => Err(IdError::AlreadyInitialized),
+        }
+    }
+
+    // 获取全局生成器，尚未配置时返回None
+    pub fn get_instance() -> Option<&'static Snowflake> {
+        GLOBAL_SNOWFLAKE.get()
+    }
+
+    // 生成下一个ID
+    // 时钟回拨不超过max_clock_skew时沿用上次的时间戳，超过时返回错误；
+    // 同一毫秒内序号用尽时借用下一毫秒，借用超过max_clock_skew时等待时钟追上
+    pub fn next_id(&self) -> Result<u64, IdError> {
+        let skew = self.config.max_clock_skew.as_millis() as u64;
+        loop {
+            // 先读状态再读时钟，被抢占的线程不会拿着过时的时间误判为时钟回拨
+            let previous = self.state.load(Ordering::Relaxed);
+            let now = self.now();
+            let (last, sequence) = (previous >> SEQUENCE_BITS, previous & MAX_SEQUENCE);
+            let behind = last.saturating_sub(now);
+            if behind > skew {
+                return Err(IdError::ClockMovedBackwards(Duration::from_millis(behind)));
+            }
+            let (timestamp, sequence) = if now > last {
+                (now, 0)
+            } else if sequence < MAX_SEQUENCE {
+                (last, sequence + 1)
+            } else {
+                (last + 1, 0)
+            };
+            if timestamp - now > skew {
+                thread::yield_now();
+                continue;
+            }
+            let next = (timestamp << SEQUENCE_BITS) | sequence;
+            if self
+                .state
+                .compare_exchange_weak(previous, next, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+            {
+                return Ok((timestamp << (NODE_BITS + SEQUENCE_BITS))
+                    | (u64::from(self.config.node_id) << SEQUENCE_BITS)
+                    | sequence);
+            }
+        }
+    }
+
+    // 拆分ID的各个组成部分
+    pub fn decompose(&self, id: u64) -> SnowflakeParts {
+        SnowflakeParts {
+            timestamp_ms: (id >> (NODE_BITS + SEQUENCE_BITS)) + self.config.epoch_ms,
+            node_id: ((id >> SEQUENCE_BITS) & u64::from(MAX_NODE_ID)) as u16,
+            sequence: (id & MAX_SEQUENCE) as u16,
+        }
+    }
+
+    // 节点号
+    pub fn node_id(&self) -> u16 {
+        self.config.node_id
+    }
+
+    // 相对于epoch的当前毫秒数
+    fn now(&self) -> u64 {
+        (self.config.clock)().saturating_sub(self.config.epoch_ms) & TIMESTAMP_MASK
+    }
+}
+
+impl fmt::Debug for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snowflake")
+            .field("node_id", &self.config.node_id)
+            .field("epoch_ms", &self.config.epoch_ms)
+            .finish()
+    }
+}
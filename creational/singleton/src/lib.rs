@@ -1,48 +1,231 @@
 // 单例模式模块入口
 // 导出五种单例模式实现
-// 注意: 方案1和方案5需要允许static_mut_refs
-#![allow(static_mut_refs)]
+// 关闭默认的std特性时为no_std: 只提供自旋锁实现的单例容器，
+// 并以Global/GlobalMutex/HotSwap的名称导出，依赖std的模块全部不编译
+// 测试时库本身同样按no_std编译，只有测试代码通过extern crate std使用标准库
+#![cfg_attr(not(feature = "std"), no_std)]
 
-// 方案1: 基本懒汉式 (非线程安全)
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// 方案1: 基本懒汉式 (GlobalMutex的包装，延迟创建，闭包访问)
+#[cfg(feature = "std")]
 mod singleton1;
+#[cfg(feature = "std")]
 pub use singleton1::Singleton1;
 
-// 方案2: 使用lazy_static宏 (线程安全)
+// 方案2: 互斥锁 + 延迟初始化 (GlobalMutex的包装，线程安全，可配置锁中毒策略)
+#[cfg(feature = "std")]
 mod singleton2;
-pub use singleton2::Singleton2;
+#[cfg(feature = "std")]
+pub use singleton2::{Singleton2, Singleton2Guard};
 
 // 方案3: 使用OnceLock (Rust 1.70+推荐方式)
+#[cfg(feature = "std")]
 mod singleton3;
+#[cfg(feature = "std")]
 pub use singleton3::Singleton3;
 
-// 方案4: 饿汉式 (线程安全)
+// 方案4: 饿汉式 (线程安全，支持读取前一次性init)
+#[cfg(feature = "std")]
 mod singleton4;
+#[cfg(feature = "std")]
 pub use singleton4::Singleton4;
 
-// 方案5: 使用std::sync::Once (线程安全的延迟初始化)
+// 方案5: 读写锁 + 一次性初始化 (GlobalRwLock的包装，线程安全的延迟初始化，读多写少)
+#[cfg(feature = "std")]
 mod singleton5;
-pub use singleton5::Singleton5;
+#[cfg(feature = "std")]
+pub use singleton5::{Singleton5, Singleton5Handle, Singleton5WriteGuard};
 
-#[cfg(test)]
+// 方案6: 线程级单例 (每个线程一个实例，可汇总所有线程的实例)
+#[cfg(feature = "std")]
+mod singleton6;
+#[cfg(feature = "std")]
+pub use singleton6::Singleton6;
+
+// 初始化诊断与访问统计: 初始化时间/耗时/线程、访问次数、等待锁的时间
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+pub use stats::{Instrument, SingletonStats, all_stats, dump_stats};
+
+// 全局配置管理: 配置文件 + 环境变量覆盖，类型化读取，必需项校验
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+pub use config::{Config, ConfigError, ConfigSource};
+
+// 对象池: 工厂闭包、容量上下限、RAII借出守卫、超时、归还时健康检查和统计
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "std")]
+pub use pool::{Pool, PoolConfig, PoolError, PoolStats, Pooled};
+
+// 全局日志: 按模块设置级别、可插拔输出(标准错误/滚动文件/环形缓冲)、结构化字段、关闭时刷新
+#[cfg(feature = "std")]
+mod logger;
+#[cfg(feature = "std")]
+pub use logger::{
+    Level, Logger, Record, RingBufferSink, RotatingFileSink, Sink, StderrSink, log_global,
+};
+
+// 全局缓存管理: 按名称管理类型化的缓存，容量上限(LRU)、TTL、命中统计、显式失效
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+pub use cache::{Cache, CacheConfig, CacheError, CacheManager, CacheStats};
+
+// 全局唯一ID: 单调递增的原子序列，Snowflake风格的64位ID(时间戳+节点号+序号)，容忍时钟回拨
+#[cfg(feature = "std")]
+mod id_gen;
+#[cfg(feature = "std")]
+pub use id_gen::{IdError, Sequence, Snowflake, SnowflakeConfig, SnowflakeParts, system_clock_ms};
+
+// 多例容器: 每个键一个懒创建的实例，同一个键只初始化一次，淘汰空闲的键
+#[cfg(feature = "std")]
+mod multiton;
+#[cfg(feature = "std")]
+pub use multiton::Multiton;
+
+// 作用域单例: 在线程或异步任务的作用域内绑定实例，嵌套时最内层生效，作用域外回退到全局实例
+#[cfg(feature = "std")]
+mod scoped;
+#[cfg(feature = "std")]
+pub use scoped::{ScopeGuard, Scoped, ScopedFuture};
+
+// 可持久化的单例: 原子写入快照文件，首次访问时恢复，定期自动保存，处理损坏和版本不匹配的快照
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+pub use snapshot::{Checkpointer, Persistent, Snapshot, SnapshotError};
+
+// 可变单例的变更通知: 订阅(旧值, 新值)，释放锁之后派发，句柄drop时取消订阅
+#[cfg(feature = "std")]
+mod notify;
+#[cfg(feature = "std")]
+pub use notify::{Notifier, Subscription};
+
+// 互斥锁中毒策略: 传播错误/恢复数据/重新初始化
+#[cfg(feature = "std")]
+mod poison;
+#[cfg(feature = "std")]
+pub use poison::{PoisonPolicy, PoisonedError};
+
+// 通用单例容器: 任意类型T的懒汉式/饿汉式/Once单例
+#[cfg(feature = "std")]
+mod global;
+#[cfg(feature = "std")]
+pub use global::Global;
+
+// 可变的通用单例容器: 任意类型T，update原子读-改-写，replace/take返回旧值，可配置锁中毒策略
+#[cfg(feature = "std")]
+mod global_mutex;
+#[cfg(feature = "std")]
+pub use global_mutex::{GlobalMutex, LockError};
+
+// 读写锁保护的通用单例容器: 读者并发，可失败初始化，可登记清理回调
+#[cfg(feature = "std")]
+mod global_rwlock;
+#[cfg(feature = "std")]
+pub use global_rwlock::GlobalRwLock;
+
+// 可失败初始化的通用单例容器: 失败后保持未初始化，可重试；环和关闭通过TryInitError返回
+#[cfg(feature = "std")]
+mod try_global;
+#[cfg(feature = "std")]
+pub use try_global::{TryGlobal, TryInitError};
+
+// 可一次性配置的饿汉式单例: const默认值 + 首次读取前的一次init
+#[cfg(feature = "std")]
+mod eager;
+#[cfg(feature = "std")]
+pub use eager::{Eager, InitError};
+
+// 线程级单例容器: 每线程懒创建，线程退出钩子，跨线程汇总
+#[cfg(feature = "std")]
+mod per_thread;
+#[cfg(feature = "std")]
+pub use per_thread::PerThread;
+
+// 异步初始化的单例容器: 并发等待者共享同一次初始化，不依赖特定运行时
+#[cfg(feature = "std")]
+mod async_global;
+#[cfg(feature = "std")]
+pub use async_global::{AsyncGlobal, BoxInit};
+
+// 按类型索引的服务注册表 (服务定位器风格的单例)
+#[cfg(feature = "std")]
+mod registry;
+#[cfg(feature = "std")]
+pub use registry::Registry;
+
+// #[singleton]属性宏: 自动生成静态存储和get_instance访问函数
+#[cfg(feature = "std")]
+pub use singleton_macros::singleton;
+
+// 初始化环检测: 初始化过程中再次访问正在初始化的单例时报告依赖链，而不是死锁
+#[cfg(feature = "std")]
+mod cycle;
+
+// 单例的有序关闭: 登记清理回调，按初始化逆序(满足依赖)执行
+#[cfg(feature = "std")]
+mod lifecycle;
+#[cfg(feature = "std")]
+pub use lifecycle::{AccessError, Lifecycle, Teardown, shutdown};
+
+// 读多写少的热替换单例: 无锁读取Arc快照，写者原子发布新值
+#[cfg(feature = "std")]
+mod hot_swap;
+#[cfg(feature = "std")]
+pub use hot_swap::HotSwap;
+
+// 自旋锁实现的同步原语和单例容器，只依赖core(和alloc)，no_std环境中可用
+mod spin;
+pub use spin::{SpinMutex, SpinMutexGuard, SpinOnce};
+mod spin_global;
+#[cfg(feature = "alloc")]
+pub use spin_global::SpinHotSwap;
+pub use spin_global::{SpinGlobal, SpinGlobalMutex};
+
+// no_std环境中以标准名称导出自旋锁实现，使用这些容器的代码在两种环境下都能编译
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub use spin_global::SpinHotSwap as HotSwap;
+#[cfg(not(feature = "std"))]
+pub use spin_global::{SpinGlobal as Global, SpinGlobalMutex as GlobalMutex};
+
+// 最小化的执行器，用于演示和测试AsyncGlobal
+#[cfg(feature = "std")]
+pub mod executor;
+
+// 测试辅助API: 重置单例、临时替换单例实例 (testing特性)
+#[cfg(all(feature = "std", any(test, feature = "testing")))]
+pub mod testing;
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
-    use std::sync::MutexGuard;
+    use std::sync::Arc;
+    use std::sync::atomic::Ordering;
 
     // 测试方案1
     #[test]
     fn test_singleton1() {
-        let instance1 = Singleton1::get_instance();
-        assert_eq!(instance1.get_data(), "Singleton1 instance");
+        let data = Singleton1::with(|instance1| instance1.get_data().to_string());
+        assert_eq!(data, "Singleton1 instance");
 
-        instance1.set_data("Updated data");
-        let instance1_again = Singleton1::get_instance();
-        assert_eq!(instance1_again.get_data(), "Updated data");
+        Singleton1::with_mut(|instance1| instance1.set_data("Updated data"));
+        Singleton1::with(|instance1_again| assert_eq!(instance1_again.get_data(), "Updated data"));
     }
 
     // 测试方案2
     #[test]
     fn test_singleton2() {
-        let mut instance2: MutexGuard<'_, Singleton2> = Singleton2::get_instance();
+        let _guard = Singleton2::isolate();
+        let mut instance2: Singleton2Guard = Singleton2::get_instance();
         assert_eq!(instance2.get_data(), "Singleton2 instance");
 
         instance2.set_data("Updated data");
@@ -50,6 +233,13 @@ mod tests {
 
         let instance2_again = Singleton2::get_instance();
         assert_eq!(instance2_again.get_data(), "Updated data");
+        drop(instance2_again);
+
+        // 读-改-写与替换
+        Singleton2::update(|data| data.push('!'));
+        assert_eq!(Singleton2::replace("replaced"), "Updated data!");
+        assert_eq!(Singleton2::take(), "replaced");
+        assert_eq!(Singleton2::get_instance().get_data(), "");
     }
 
     // 测试方案3
@@ -66,17 +256,34 @@ mod tests {
     fn test_singleton4() {
         let instance4 = Singleton4::get_instance();
         assert_eq!(instance4.get_data(), "");
+
+        // 已经读取过实例，init不能再修改数据
+        assert!(matches!(
+            Singleton4::init("late data"),
+            Err(InitError::AlreadyObserved)
+        ));
+        assert_eq!(Singleton4::get_instance().get_data(), "");
     }
 
     // 测试方案5
     #[test]
     fn test_singleton5() {
-        let instance5 = Singleton5::get_instance();
+        let _guard = Singleton5::isolate();
+        let mut instance5 = Singleton5::write();
         assert_eq!(instance5.get_data(), "Singleton5 instance");
 
         instance5.set_data("Updated data");
-        let instance5_again = Singleton5::get_instance();
+        drop(instance5); // 释放写锁
+
+        let instance5_again = Singleton5::read();
         assert_eq!(instance5_again.get_data(), "Updated data");
+        drop(instance5_again);
+
+        // 读-改-写与替换
+        Singleton5::update(|data| data.push('!'));
+        assert_eq!(Singleton5::replace("replaced"), "Updated data!");
+        assert_eq!(Singleton5::take(), "replaced");
+        assert_eq!(Singleton5::read().get_data(), "");
     }
 
     // 测试线程安全性
@@ -85,26 +292,37 @@ mod tests {
         use std::thread;
         use std::thread::JoinHandle;
 
+        // 独占单例，避免与其他测试互相干扰 (按固定顺序获取，避免死锁)
+        let _guard2 = Singleton2::isolate();
+        let _guard5 = Singleton5::isolate();
+        static COUNTER: GlobalMutex<u64> = GlobalMutex::new(|| 0);
+
         // 测试多种单例的线程安全性
         let mut handles: Vec<JoinHandle<()>> = Vec::new();
         for i in 0..10 {
             let handle = thread::spawn(move || {
                 let data = format!("Thread {} data", i);
-                
+
                 // 测试方案2
                 let mut instance2 = Singleton2::get_instance();
                 instance2.set_data(&data);
                 println!("Thread {} set singleton2 data: {}", i, instance2.get_data());
                 drop(instance2);
-                
+
                 // 测试方案5
-                let instance5 = Singleton5::get_instance();
+                let mut instance5 = Singleton5::write();
                 instance5.set_data(&data);
                 println!("Thread {} set singleton5 data: {}", i, instance5.get_data());
+                drop(instance5);
+
+                // 读-改-写在一次加锁内完成，计数不会丢失
+                for _ in 0..100 {
+                    COUNTER.update(|count| *count += 1);
+                }
             });
             handles.push(handle);
         }
-        
+
         for handle in handles {
             handle.join().unwrap();
         }
@@ -112,8 +330,2316 @@ mod tests {
         // 验证最后设置的数据
         let final_instance2 = Singleton2::get_instance();
         println!("Final singleton2 data: {}", final_instance2.get_data());
-        
-        let final_instance5 = Singleton5::get_instance();
+
+        let final_instance5 = Singleton5::read();
         println!("Final singleton5 data: {}", final_instance5.get_data());
+        assert_eq!(COUNTER.get(), 1000);
+    }
+
+    // 测试可变的通用单例容器: 任意类型、读-改-写、替换和取出
+    #[test]
+    fn test_global_mutex() {
+        static QUEUE: GlobalMutex<Vec<&str>> = GlobalMutex::new(|| vec!["init"]);
+
+        assert!(!QUEUE.is_initialized());
+        let len = QUEUE.update(|queue| {
+            queue.push("job");
+            queue.len()
+        });
+        assert_eq!(len, 2);
+        assert_eq!(QUEUE.replace(vec!["fresh"]), ["init", "job"]);
+        assert_eq!(QUEUE.take(), ["fresh"]);
+        assert!(QUEUE.get().is_empty());
+        QUEUE.lock().push("locked");
+        assert_eq!(format!("{:?}", QUEUE), r#"GlobalMutex(["locked"])"#);
+    }
+
+    // 测试GlobalMutex的锁中毒策略: 默认继续使用panic时留下的数据
+    #[test]
+    fn test_global_mutex_poison_policy() {
+        static COUNTER: GlobalMutex<u32> =
+            GlobalMutex::<u32>::new(|| 1).with_poison_policy(PoisonPolicy::Propagate);
+        let poison = || {
+            let result = std::thread::spawn(|| {
+                let mut count = COUNTER.lock();
+                *count += 1;
+                panic!("panic while holding COUNTER");
+            })
+            .join();
+            assert!(result.is_err());
+        };
+
+        poison();
+        assert_eq!(COUNTER.poison_policy(), PoisonPolicy::Propagate);
+        assert!(COUNTER.try_lock().is_err());
+        assert!(std::panic::catch_unwind(|| COUNTER.get()).is_err());
+
+        COUNTER.set_poison_policy(PoisonPolicy::Recover);
+        assert_eq!(*COUNTER.try_lock().unwrap(), 2);
+
+        poison();
+        COUNTER.set_poison_policy(PoisonPolicy::Reinitialize);
+        assert_eq!(COUNTER.get(), 1);
+        COUNTER.set_poison_policy(PoisonPolicy::Propagate);
+        assert!(COUNTER.try_lock().is_ok());
+
+        // 默认策略为Recover，与PoisonPolicy::default()一致
+        static QUEUE: GlobalMutex<Vec<u32>> = GlobalMutex::new(Vec::new);
+        assert_eq!(QUEUE.poison_policy(), PoisonPolicy::Recover);
+        assert_eq!(QUEUE.poison_policy(), PoisonPolicy::default());
+    }
+
+    // 测试读写锁保护的通用单例容器: 任意类型、可失败初始化后重试、清理后拒绝访问
+    #[test]
+    fn test_global_rwlock() {
+        use std::sync::atomic::AtomicUsize;
+
+        static LIFECYCLE: Lifecycle = Lifecycle::new();
+        static CLOSED_WITH: AtomicUsize = AtomicUsize::new(0);
+        static ROUTES: GlobalRwLock<Vec<&str>> = GlobalRwLock::<Vec<&str>>::new(|| vec!["/"])
+            .with_teardown_in(
+                Teardown {
+                    name: "routes",
+                    depends_on: &[],
+                    hook: |routes| CLOSED_WITH.store(routes.len(), Ordering::SeqCst),
+                },
+                &LIFECYCLE,
+            );
+
+        // 初始化失败时保持未初始化，之后可以重试
+        assert_eq!(
+            ROUTES.get_or_try_init(|| Err("routes file missing")).err(),
+            Some(TryInitError::Init("routes file missing"))
+        );
+        assert!(!ROUTES.is_initialized());
+        assert_eq!(ROUTES.last_error().as_deref(), Some("routes file missing"));
+        let routes = ROUTES
+            .get_or_try_init(|| Ok::<_, &str>(vec!["/", "/health"]))
+            .unwrap();
+        assert!(std::ptr::eq(routes, ROUTES.get()));
+        assert_eq!(ROUTES.read().len(), 2);
+        // 已初始化时不再调用初始化闭包
+        assert!(ROUTES.get_or_try_init(|| Err("unused")).is_ok());
+
+        ROUTES.update(|routes| routes.push("/metrics"));
+        assert_eq!(ROUTES.replace(vec!["/v2"]), ["/", "/health", "/metrics"]);
+        ROUTES.write().push("/v2/health");
+        assert_eq!(
+            format!("{:?}", ROUTES),
+            r#"GlobalRwLock(["/v2", "/v2/health"])"#
+        );
+
+        // 清理回调可以读取实例，之后的访问返回错误
+        assert_eq!(LIFECYCLE.shutdown(), ["routes"]);
+        assert_eq!(CLOSED_WITH.load(Ordering::SeqCst), 2);
+        assert!(ROUTES.is_shut_down());
+        assert!(matches!(
+            ROUTES.try_get(),
+            Err(AccessError::ShutDown("routes"))
+        ));
+        assert_eq!(
+            ROUTES.get_or_try_init(|| Err("unused")).err(),
+            Some(TryInitError::Access(AccessError::ShutDown("routes")))
+        );
+
+        // 初始化过程中再次访问本单例时返回错误而不是死锁
+        static SELF_REF: GlobalRwLock<u32> = GlobalRwLock::new(|| {
+            assert!(matches!(SELF_REF.try_get(), Err(AccessError::Cycle(_))));
+            7
+        });
+        assert_eq!(*SELF_REF.read(), 7);
+    }
+
+    // 测试读写锁单例的并发首次访问: 初始化闭包只执行一次，统计只记录一次初始化
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_global_rwlock_concurrent_init() {
+        use std::sync::Barrier;
+        use std::sync::atomic::AtomicUsize;
+        use std::thread;
+        use std::time::Duration;
+
+        static INITS: AtomicUsize = AtomicUsize::new(0);
+        static SHARED: GlobalRwLock<usize> = GlobalRwLock::instrumented(
+            || {
+                thread::sleep(Duration::from_millis(20));
+                INITS.fetch_add(1, Ordering::SeqCst)
+            },
+            "test_global_rwlock_concurrent_init",
+        );
+
+        let barrier = Arc::new(Barrier::new(8));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    *SHARED.read()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 0);
+        }
+        assert_eq!(INITS.load(Ordering::SeqCst), 1);
+        assert!(SHARED.stats().unwrap().initialized_at.is_some());
+    }
+
+    // 测试通用单例容器: 懒汉式
+    #[test]
+    fn test_global_lazy() {
+        static CONFIG: Global<Vec<String>> = Global::new(|| vec!["a".to_string(), "b".to_string()]);
+
+        assert!(!CONFIG.is_initialized());
+        assert!(CONFIG.get_if_initialized().is_none());
+        assert_eq!(CONFIG.get().len(), 2);
+        assert!(CONFIG.is_initialized());
+        // 多次获取得到同一个实例
+        assert!(std::ptr::eq(CONFIG.get(), CONFIG.get()));
+        // 支持Deref直接访问
+        assert_eq!(CONFIG[1], "b");
+    }
+
+    // 测试通用单例容器: 饿汉式 (提前调用force初始化)
+    #[test]
+    fn test_global_eager() {
+        static NAME: Global<String> = Global::new(|| "eager".to_string());
+
+        let forced = NAME.force();
+        assert!(NAME.is_initialized());
+        assert!(std::ptr::eq(forced, NAME.get()));
+        assert_eq!(NAME.get_if_initialized().map(String::as_str), Some("eager"));
+    }
+
+    // 测试通用单例容器: 多线程下初始化闭包只执行一次
+    #[test]
+    fn test_global_init_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread;
+
+        static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+        static SHARED: Global<usize> =
+            Global::new(|| INIT_COUNT.fetch_add(1, Ordering::SeqCst) + 100);
+
+        let handles: Vec<_> = (0..10).map(|_| thread::spawn(|| *SHARED.get())).collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 100);
+        }
+        assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 1);
+    }
+
+    // 测试通用单例容器: 初始化闭包panic后可以重试
+    #[test]
+    fn test_global_init_panic_retry() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        static FAIL: AtomicBool = AtomicBool::new(true);
+        static FLAKY: Global<u32> = Global::new(|| {
+            if FAIL.swap(false, Ordering::SeqCst) {
+                panic!("first init fails");
+            }
+            42
+        });
+
+        let result = std::panic::catch_unwind(|| *FLAKY.get());
+        assert!(result.is_err());
+        assert!(!FLAKY.is_initialized());
+        assert_eq!(*FLAKY.get(), 42);
+    }
+
+    // 测试可失败初始化: 失败后保持未初始化并记录错误，之后可以重试
+    #[test]
+    fn test_try_global_retry() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        static RESOURCE: TryGlobal<String, String> =
+            TryGlobal::new(|| match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+                0 => Err("resource not ready".to_string()),
+                n => Ok(format!("loaded after {} attempts", n + 1)),
+            });
+
+        assert_eq!(
+            RESOURCE.get(),
+            Err(TryInitError::Init("resource not ready".to_string()))
+        );
+        assert!(!RESOURCE.is_initialized());
+        assert_eq!(RESOURCE.last_error().as_deref(), Some("resource not ready"));
+
+        assert_eq!(RESOURCE.get().unwrap(), "loaded after 2 attempts");
+        assert!(RESOURCE.is_initialized());
+        // 初始化成功后不再执行初始化闭包
+        assert_eq!(RESOURCE.get().unwrap(), "loaded after 2 attempts");
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+    }
+
+    // 测试方案3和方案5的可失败初始化: 已初始化时不再执行初始化闭包
+    // 其他测试可能已经初始化了实例，失败后重试、环检测和关闭之后的访问在tests/try_get_instance.rs中
+    // 以独立进程测试，这里只测试已初始化的情况
+    #[test]
+    fn test_try_get_instance() {
+        let instance3 = Singleton3::get_instance();
+        let again3 = Singleton3::try_get_instance(|| Err::<String, _>("unused")).unwrap();
+        assert!(std::ptr::eq(instance3, again3));
+
+        let instance5 = Singleton5::get_instance();
+        let again5 = Singleton5::try_get_instance(|| Err::<String, _>("unused")).unwrap();
+        assert_eq!(instance5, again5);
+    }
+
+    // 测试方案3和GlobalRwLock(方案5)共用的可失败初始化: 使用私有的static，不受其他测试影响
+    #[test]
+    fn test_try_init_retry() {
+        use crate::try_global::TryInit;
+        use std::sync::OnceLock;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static INSTANCE: OnceLock<String> = OnceLock::new();
+        static TRY_INIT: TryInit = TryInit::new();
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        let try_get = |init: fn() -> Result<String, &'static str>| {
+            TRY_INIT.run(
+                "flaky",
+                || INSTANCE.get(),
+                || {
+                    ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+                    init()
+                },
+                |data| INSTANCE.get_or_init(|| data),
+            )
+        };
+
+        // 失败: 保持未初始化并记录错误
+        assert_eq!(
+            try_get(|| Err("config missing")),
+            Err(TryInitError::Init("config missing"))
+        );
+        assert!(INSTANCE.get().is_none());
+        assert_eq!(TRY_INIT.last_error().as_deref(), Some("config missing"));
+        assert_eq!(
+            try_get(|| Err("still missing")),
+            Err(TryInitError::Init("still missing"))
+        );
+        assert_eq!(TRY_INIT.last_error().as_deref(), Some("still missing"));
+
+        // 重试成功，之后不再执行初始化闭包，错误信息保留以便排查
+        let instance = try_get(|| Ok("loaded".to_string())).unwrap();
+        assert_eq!(instance, "loaded");
+        assert!(std::ptr::eq(instance, try_get(|| Err("unused")).unwrap()));
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 3);
+        assert_eq!(TRY_INIT.last_error().as_deref(), Some("still missing"));
+    }
+
+    // 测试方案5的读写锁: 每次获取的都是同一个实例的共享引用，读者之间不存在可变别名
+    // 该测试与test_thread_safety一起可在Miri下运行 (命令见README方案5的注意事项)，
+    // 访问文件系统、环境变量或大量sleep的测试标记了cfg_attr(miri, ignore)
+    #[test]
+    fn test_singleton5_shared_readers() {
+        use std::thread;
+
+        assert_eq!(Singleton5::get_instance(), Singleton5::get_instance());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| Singleton5::with(|instance5| !instance5.get_data().is_empty()))
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+
+    // 测试可一次性配置的饿汉式单例
+    #[test]
+    fn test_eager_init_once() {
+        static PORT: Eager<u16> = Eager::new(8080);
+
+        assert!(!PORT.is_configured());
+        assert_eq!(PORT.init(9000), Ok(&9000));
+        assert!(PORT.is_configured());
+        assert_eq!(PORT.init(9001), Err(InitError::AlreadyInitialized));
+        assert_eq!(*PORT.get(), 9000);
+    }
+
+    // 测试饿汉式单例: 未调用init时使用默认值，读取后不能再init
+    #[test]
+    fn test_eager_default_after_read() {
+        static LEVEL: Eager<&str> = Eager::new("info");
+
+        assert_eq!(*LEVEL.get(), "info");
+        assert_eq!(LEVEL.init("debug"), Err(InitError::AlreadyObserved));
+        assert!(!LEVEL.is_configured());
+        assert_eq!(*LEVEL.get(), "info");
+    }
+
+    // 测试重置单例: 重置后重新执行初始化闭包，旧引用仍然有效
+    #[test]
+    fn test_global_reset() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static GENERATION: AtomicUsize = AtomicUsize::new(0);
+        static SESSION: Global<usize> = Global::new(|| GENERATION.fetch_add(1, Ordering::SeqCst));
+
+        let first = SESSION.get();
+        assert_eq!(*first, 0);
+        SESSION.reset();
+        assert!(!SESSION.is_initialized());
+        assert_eq!(*SESSION.get(), 1);
+        assert_eq!(*first, 0);
+    }
+
+    // 测试临时替换单例: 守卫析构后恢复原实例
+    #[test]
+    fn test_global_override() {
+        static ENDPOINT: Global<String> = Global::new(|| "https://prod".to_string());
+
+        assert_eq!(ENDPOINT.get(), "https://prod");
+        {
+            let _guard = ENDPOINT.override_with("http://mock".to_string());
+            assert_eq!(ENDPOINT.get(), "http://mock");
+        }
+        assert_eq!(ENDPOINT.get(), "https://prod");
+
+        // 替换尚未初始化的单例，恢复后仍为未初始化
+        static LAZY: TryGlobal<u8, ()> = TryGlobal::new(|| Ok(1));
+        {
+            let _guard = LAZY.override_with(2);
+            assert_eq!(LAZY.get(), Ok(&2));
+        }
+        assert!(!LAZY.is_initialized());
+        assert_eq!(LAZY.get(), Ok(&1));
+    }
+
+    // 测试替换非static的单例: 守卫被遗忘时替换用的实例留在容器中，容器析构时按分配方式释放
+    #[test]
+    fn test_global_override_then_drop() {
+        use std::mem;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Tracked(Arc<AtomicUsize>);
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&dropped);
+        let global = Global::new(move || Tracked(Arc::clone(&counter)));
+        global.get();
+        mem::forget(global.override_with(Tracked(Arc::clone(&dropped))));
+        drop(global);
+        // 被替换下来的原实例被泄漏，替换用的实例随容器释放
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&dropped);
+        let try_global = TryGlobal::new(move || Ok::<_, ()>(Tracked(Arc::clone(&counter))));
+        assert!(try_global.get().is_ok());
+        mem::forget(try_global.override_with(Tracked(Arc::clone(&dropped))));
+        drop(try_global);
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+    }
+
+    // 测试替换守卫的独占性: 并行替换同一单例的测试依次执行，各自看到自己的实例
+    #[test]
+    fn test_global_override_serialized() {
+        use std::thread;
+
+        static COUNTER: Global<usize> = Global::new(|| 0);
+
+        let handles: Vec<_> = (1..=4)
+            .map(|i| {
+                thread::spawn(move || {
+                    let _guard = COUNTER.override_with(i);
+                    thread::yield_now();
+                    *COUNTER.get()
+                })
+            })
+            .collect();
+        let mut seen: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        seen.sort();
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+        assert_eq!(*COUNTER.get(), 0);
+    }
+
+    // 测试方案2和方案5的重置
+    #[test]
+    fn test_singleton_reset() {
+        let _guard2 = Singleton2::isolate();
+        let _guard5 = Singleton5::isolate();
+
+        Singleton2::get_instance().set_data("dirty");
+        Singleton2::reset();
+        assert_eq!(Singleton2::get_instance().get_data(), "Singleton2 instance");
+
+        Singleton5::write().set_data("dirty");
+        Singleton5::reset();
+        assert_eq!(Singleton5::read().get_data(), "Singleton5 instance");
+    }
+
+    // 测试方案6: 每个线程拥有独立实例，可以汇总所有线程的计数
+    #[test]
+    fn test_singleton6() {
+        use std::sync::{Arc, Barrier};
+        use std::thread;
+
+        let main_count = Singleton6::with(|instance6| {
+            assert!(instance6.get_data().starts_with("Singleton6 instance of"));
+            instance6.increment()
+        });
+        assert_eq!(Singleton6::with(Singleton6::get_count), main_count);
+
+        // 工作线程计数完成后保持存活，直到主线程完成汇总
+        let counted = Arc::new(Barrier::new(5));
+        let summed = Arc::new(Barrier::new(5));
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let counted = Arc::clone(&counted);
+                let summed = Arc::clone(&summed);
+                thread::Builder::new()
+                    .name(format!("worker-{}", i))
+                    .spawn(move || {
+                        Singleton6::with(|instance6| {
+                            assert_eq!(
+                                instance6.get_data(),
+                                format!("Singleton6 instance of worker-{}", i)
+                            );
+                            for _ in 0..10 {
+                                instance6.increment();
+                            }
+                        });
+                        counted.wait();
+                        summed.wait();
+                    })
+                    .unwrap()
+            })
+            .collect();
+
+        counted.wait();
+        assert!(Singleton6::live_instances() >= 5);
+        assert!(Singleton6::live_count_sum() >= 40 + main_count);
+        let total_before_exit = Singleton6::total_count();
+        summed.wait();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        // 线程退出后计数通过析构钩子转移到已退出总和中，总数不会减少
+        assert!(Singleton6::total_count() >= total_before_exit);
+    }
+
+    // 测试线程级单例容器: 线程退出时执行析构钩子并从存活列表中移除
+    #[test]
+    fn test_per_thread_exit_hook() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread;
+
+        static EXITED: AtomicUsize = AtomicUsize::new(0);
+        static SCRATCH: PerThread<Vec<u8>> = PerThread::with_exit_hook(
+            || vec![0; 16],
+            |buffer| {
+                EXITED.fetch_add(buffer.len(), Ordering::SeqCst);
+            },
+        );
+
+        let local = SCRATCH.get();
+        assert!(Arc::ptr_eq(&local, &SCRATCH.get()));
+
+        let remote = thread::spawn(|| SCRATCH.get()).join().unwrap();
+        assert!(!Arc::ptr_eq(&local, &remote));
+        assert_eq!(EXITED.load(Ordering::SeqCst), 16);
+        assert_eq!(SCRATCH.live_count(), 1);
+        assert_eq!(SCRATCH.fold(0, |sum, buffer| sum + buffer.len()), 16);
+
+        let mut seen = 0;
+        SCRATCH.for_each(|_| seen += 1);
+        assert_eq!(seen, 1);
+    }
+
+    // 测试异步单例: 多个线程并发等待，只执行一次初始化
+    #[test]
+    fn test_async_global_shared_init() {
+        use std::future::poll_fn;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::task::Poll;
+        use std::thread;
+
+        static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+        static POOL: AsyncGlobal<String> = AsyncGlobal::new(|| {
+            Box::pin(async {
+                INIT_COUNT.fetch_add(1, Ordering::SeqCst);
+                // 模拟异步IO: 让出若干次执行权
+                let mut remaining = 3;
+                poll_fn(|cx| {
+                    if remaining == 0 {
+                        return Poll::Ready(());
+                    }
+                    remaining -= 1;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                })
+                .await;
+                "connection pool".to_string()
+            })
+        });
+
+        assert!(!POOL.is_initialized());
+        let handles: Vec<_> = (0..8)
+            .map(|_| thread::spawn(|| executor::block_on(POOL.get()) as *const String as usize))
+            .collect();
+        let addresses: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(addresses.windows(2).all(|pair| pair[0] == pair[1]));
+        assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            POOL.get_if_initialized().map(String::as_str),
+            Some("connection pool")
+        );
+    }
+
+    // 测试异步单例: 负责初始化的任务被取消后，等待者接手重新初始化
+    #[test]
+    fn test_async_global_cancel_takeover() {
+        use std::future::{Future, poll_fn};
+        use std::pin::pin;
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::task::{Context, Poll, Waker};
+
+        static READY: AtomicBool = AtomicBool::new(false);
+        static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+        static CONFIG: AsyncGlobal<usize> = AsyncGlobal::new(|| {
+            Box::pin(async {
+                let attempt = INIT_COUNT.fetch_add(1, Ordering::SeqCst) + 1;
+                poll_fn(|_| {
+                    if READY.load(Ordering::SeqCst) {
+                        Poll::Ready(())
+                    } else {
+                        Poll::Pending
+                    }
+                })
+                .await;
+                attempt
+            })
+        });
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut first = Box::pin(CONFIG.get());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        let mut second = pin!(CONFIG.get());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 1);
+
+        // 取消负责初始化的任务，第二个任务接手
+        drop(first);
+        READY.store(true, Ordering::SeqCst);
+        assert_eq!(second.as_mut().poll(&mut cx), Poll::Ready(&2));
+        assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 2);
+        assert_eq!(executor::block_on(CONFIG.get()), &2);
+    }
+
+    // 测试服务注册表: 每种类型一个实例
+    #[test]
+    fn test_registry_register_and_get() {
+        #[derive(Debug, PartialEq)]
+        struct Database {
+            url: String,
+        }
+        struct Cache;
+
+        let registry = Registry::new();
+        assert!(registry.is_empty());
+        assert!(registry.get::<Database>().is_none());
+
+        let db = registry
+            .register(Database {
+                url: "postgres://localhost".to_string(),
+            })
+            .unwrap();
+        assert!(Arc::ptr_eq(&db, &registry.get::<Database>().unwrap()));
+
+        // 同一类型不能重复注册，交还被拒绝的实例
+        let rejected = registry.register(Database {
+            url: "mysql://localhost".to_string(),
+        });
+        assert_eq!(rejected.unwrap_err().url, "mysql://localhost");
+        assert_eq!(
+            registry.get::<Database>().unwrap().url,
+            "postgres://localhost"
+        );
+
+        registry.get_or_init(|| Cache);
+        assert!(registry.contains::<Cache>());
+        assert_eq!(registry.len(), 2);
+        let types = registry.registered_types();
+        assert!(types.iter().any(|name| name.ends_with("Database")));
+        assert!(types.iter().any(|name| name.ends_with("Cache")));
+    }
+
+    // 测试服务注册表: 并发get_or_init只初始化一次，初始化闭包中可以访问其他类型
+    #[test]
+    fn test_registry_get_or_init_concurrent() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread;
+
+        struct Settings(u32);
+        struct Service(u32);
+
+        static REGISTRY: Registry = Registry::new();
+        static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(|| {
+                    REGISTRY.get_or_init(|| {
+                        INIT_COUNT.fetch_add(1, Ordering::SeqCst);
+                        let settings = REGISTRY.get_or_init(|| Settings(7));
+                        Service(settings.0 * 6)
+                    })
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().0, 42);
+        }
+        assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 1);
+        assert_eq!(REGISTRY.len(), 2);
+
+        // 全局注册表
+        struct GlobalMarker;
+        Registry::global().get_or_init(|| GlobalMarker);
+        assert!(Registry::global().contains::<GlobalMarker>());
+    }
+
+    // 测试#[singleton]属性宏: 四种策略
+    #[test]
+    fn test_singleton_macro() {
+        use std::cell::Cell;
+        use std::thread;
+
+        #[singleton(strategy = "once_lock")]
+        #[derive(Default)]
+        struct AppInfo {
+            name: String,
+        }
+        assert_eq!(AppInfo::get_instance().name, "");
+        assert!(std::ptr::eq(
+            AppInfo::get_instance(),
+            AppInfo::get_instance()
+        ));
+
+        fn new_counter() -> Counter {
+            Counter { value: 10 }
+        }
+        #[singleton(strategy = "mutex", init = new_counter)]
+        struct Counter {
+            value: u32,
+        }
+        Counter::get_instance().value += 1;
+        Counter::with_mut(|counter| counter.value += 1);
+        assert_eq!(Counter::with(|counter| counter.value), 12);
+
+        const fn new_limits() -> Limits {
+            Limits { max: 64 }
+        }
+        #[singleton(strategy = "eager", init = "new_limits")]
+        struct Limits {
+            max: usize,
+        }
+        assert_eq!(Limits::get_instance().max, 64);
+
+        #[singleton(strategy = "thread_local")]
+        #[derive(Default)]
+        struct Scratch {
+            uses: Cell<u32>,
+        }
+        Scratch::with(|scratch| scratch.uses.set(scratch.uses.get() + 1));
+        assert_eq!(Scratch::with(|scratch| scratch.uses.get()), 1);
+        let other = thread::spawn(|| Scratch::with(|scratch| scratch.uses.get()))
+            .join()
+            .unwrap();
+        assert_eq!(other, 0);
+    }
+
+    // 测试有序关闭: 按初始化逆序执行，声明的依赖优先
+    #[test]
+    fn test_lifecycle_shutdown_order() {
+        let lifecycle = Lifecycle::new();
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        for (name, depends_on) in [
+            ("logger", &[][..]),
+            ("database", &["logger", "config"][..]),
+            // config在database之后初始化，但database声明了依赖config
+            ("config", &[][..]),
+            ("cache", &["database"][..]),
+        ] {
+            let log = Arc::clone(&log);
+            lifecycle
+                .register(name, depends_on, move || log.lock().unwrap().push(name))
+                .unwrap();
+        }
+        assert_eq!(
+            lifecycle.registered(),
+            vec!["logger", "database", "config", "cache"]
+        );
+
+        let order = lifecycle.shutdown();
+        assert_eq!(order, vec!["cache", "database", "config", "logger"]);
+        assert_eq!(*log.lock().unwrap(), order);
+
+        // 关闭之后不能再登记，重复关闭不再执行回调
+        assert!(lifecycle.is_shut_down());
+        assert_eq!(
+            lifecycle.register("late", &[], || {}),
+            Err(AccessError::ShutDown("late"))
+        );
+        assert!(lifecycle.shutdown().is_empty());
+    }
+
+    // 测试有序关闭: 回调panic不影响其余回调
+    #[test]
+    fn test_lifecycle_teardown_panic() {
+        let lifecycle = Lifecycle::new();
+        let ran = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = Arc::clone(&ran);
+        lifecycle
+            .register("first", &[], move || flag.store(true, Ordering::SeqCst))
+            .unwrap();
+        lifecycle
+            .register("second", &[], || panic!("teardown failed"))
+            .unwrap();
+
+        assert_eq!(lifecycle.shutdown(), vec!["second", "first"]);
+        assert!(ran.load(Ordering::SeqCst));
+    }
+
+    // 测试受管理的Global: shutdown之后访问返回错误
+    // 使用独立的生命周期管理器，进程级的管理器不能在并行的测试中关闭
+    #[test]
+    fn test_global_with_teardown() {
+        use std::sync::atomic::AtomicUsize;
+
+        static LIFECYCLE: Lifecycle = Lifecycle::new();
+        static CLOSED: AtomicUsize = AtomicUsize::new(0);
+        static DATABASE: Global<String> = Global::with_teardown_in(
+            || "db connection".to_string(),
+            Teardown {
+                name: "database",
+                depends_on: &["config"],
+                hook: |_| {
+                    CLOSED.fetch_add(1, Ordering::SeqCst);
+                },
+            },
+            &LIFECYCLE,
+        );
+        static CONFIG: Global<u32> = Global::with_teardown_in(
+            || 3,
+            Teardown {
+                name: "config",
+                depends_on: &[],
+                hook: |_| {},
+            },
+            &LIFECYCLE,
+        );
+        static NEVER_USED: Global<u32> = Global::with_teardown_in(
+            || 0,
+            Teardown {
+                name: "never_used",
+                depends_on: &[],
+                hook: |_| {},
+            },
+            &LIFECYCLE,
+        );
+        // with_teardown登记在进程级的管理器中
+        static SESSION: Global<u32> = Global::with_teardown(
+            || 1,
+            Teardown {
+                name: "with-teardown-session",
+                depends_on: &[],
+                hook: |_| {},
+            },
+        );
+
+        assert_eq!(*CONFIG.get(), 3);
+        assert_eq!(DATABASE.try_get().map(String::as_str), Ok("db connection"));
+        assert_eq!(*SESSION.get(), 1);
+        assert_eq!(LIFECYCLE.registered(), ["config", "database"]);
+        assert!(
+            Lifecycle::global()
+                .registered()
+                .contains(&"with-teardown-session")
+        );
+
+        assert_eq!(LIFECYCLE.shutdown(), ["database", "config"]);
+        assert_eq!(CLOSED.load(Ordering::SeqCst), 1);
+
+        assert!(DATABASE.is_shut_down());
+        assert_eq!(DATABASE.try_get(), Err(AccessError::ShutDown("database")));
+        assert!(DATABASE.get_if_initialized().is_none());
+        // 关闭之后不再创建新的受管理实例
+        assert_eq!(
+            NEVER_USED.try_get(),
+            Err(AccessError::ShutDown("never_used"))
+        );
+        // 关闭之后才初始化的单例同样被报告为已关闭
+        assert!(NEVER_USED.is_shut_down());
+        assert!(!NEVER_USED.is_initialized());
+        assert!(std::panic::catch_unwind(|| CONFIG.get()).is_err());
+        // 其他管理器中的单例不受影响
+        assert!(!SESSION.is_shut_down());
+        assert_eq!(*SESSION.get(), 1);
+    }
+
+    // 测试方案2的锁中毒策略: 持有锁的线程panic之后
+    #[test]
+    fn test_singleton2_poison_policy() {
+        use std::thread;
+
+        let _guard = Singleton2::isolate();
+        // 持有锁时panic，使锁中毒
+        let poison = |data: &'static str| {
+            let result = thread::spawn(move || {
+                let mut instance2 = Singleton2::get_instance();
+                instance2.set_data(data);
+                panic!("panic while holding Singleton2");
+            })
+            .join();
+            assert!(result.is_err());
+        };
+
+        // 传播: 返回类型化的错误，get_instance会panic
+        Singleton2::set_poison_policy(PoisonPolicy::Propagate);
+        poison("half written");
+        assert_eq!(
+            Singleton2::try_get_instance().err(),
+            Some(PoisonedError { name: "Singleton2" })
+        );
+        assert!(std::panic::catch_unwind(Singleton2::get_instance).is_err());
+
+        // 恢复: 继续使用panic时留下的数据，并清除中毒状态
+        Singleton2::set_poison_policy(PoisonPolicy::Recover);
+        assert_eq!(
+            Singleton2::try_get_instance().unwrap().get_data(),
+            "half written"
+        );
+        Singleton2::set_poison_policy(PoisonPolicy::Propagate);
+        assert!(Singleton2::try_get_instance().is_ok());
+
+        // 重新初始化: 丢弃panic时留下的数据
+        poison("corrupted");
+        Singleton2::set_poison_policy(PoisonPolicy::Reinitialize);
+        assert_eq!(
+            Singleton2::try_get_instance().unwrap().get_data(),
+            "Singleton2 instance"
+        );
+        Singleton2::set_poison_policy(PoisonPolicy::Propagate);
+        assert_eq!(Singleton2::poison_policy(), PoisonPolicy::Propagate);
+        assert!(Singleton2::try_get_instance().is_ok());
+    }
+
+    // 测试热替换单例: 旧快照在替换后保持有效，读者最终看到新值
+    #[test]
+    fn test_hot_swap() {
+        use std::thread;
+
+        static SETTINGS: HotSwap<Vec<u32>> = HotSwap::new(|| vec![1]);
+
+        assert!(!SETTINGS.is_initialized());
+        let old = SETTINGS.load();
+        assert_eq!(*old, [1]);
+        assert!(Arc::ptr_eq(&old, &SETTINGS.load()));
+
+        SETTINGS.store(vec![2]);
+        assert_eq!(*old, [1]);
+        assert_eq!(*SETTINGS.load(), [2]);
+        assert_eq!(*SETTINGS.swap(vec![3]).unwrap(), [2]);
+
+        // 并发读取与基于旧值的更新: 更新不会丢失，读者看到的值单调递增
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    let mut last = 0;
+                    for _ in 0..1000 {
+                        let current = SETTINGS.load()[0];
+                        assert!(current >= last);
+                        last = current;
+                    }
+                })
+            })
+            .collect();
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    for _ in 0..100 {
+                        SETTINGS.update(|value| vec![value[0] + 1]);
+                    }
+                })
+            })
+            .collect();
+        for handle in readers.into_iter().chain(writers) {
+            handle.join().unwrap();
+        }
+        assert_eq!(*SETTINGS.load(), [403]);
+        assert_eq!(*old, [1]);
+
+        // 更新闭包panic时当前值保持不变，不会被初始化闭包的默认值覆盖
+        let panicked = std::panic::catch_unwind(|| {
+            SETTINGS.update(|_| panic!("invalid settings"));
+        });
+        assert!(panicked.is_err());
+        assert_eq!(*SETTINGS.load(), [403]);
+        assert_eq!(*SETTINGS.update(|value| vec![value[0] + 1]), [404]);
+    }
+
+    // 测试热替换单例: 线程本地缓存不会延长被替换值的生命周期
+    #[test]
+    fn test_hot_swap_releases_old_values() {
+        use std::sync::atomic::AtomicUsize;
+
+        static INITS: AtomicUsize = AtomicUsize::new(0);
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Connection;
+        impl Drop for Connection {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        fn connect() -> Connection {
+            INITS.fetch_add(1, Ordering::SeqCst);
+            Connection
+        }
+
+        // 尚未初始化时swap不会为了构造旧值调用init
+        let pool = HotSwap::new(connect);
+        assert!(pool.swap(Connection).is_none());
+        assert_eq!(INITS.load(Ordering::SeqCst), 0);
+
+        // 读者drop快照后，被替换的值立即释放
+        drop(pool.load());
+        pool.store(connect());
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        let snapshot = pool.load();
+        drop(pool.swap(Connection));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        drop(snapshot);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+        // 非static的HotSwap被drop时释放当前值
+        drop(pool.load());
+        drop(pool);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+    }
+
+    // 测试初始化诊断与访问统计
+    #[test]
+    fn test_stats() {
+        use std::thread;
+
+        static CONFIG: Global<String> =
+            Global::instrumented(|| "config".to_string(), "stats-config");
+        static COUNTER: GlobalMutex<u64> = GlobalMutex::instrumented(|| 0, "stats-counter");
+        static FLAKY: TryGlobal<u8, String> =
+            TryGlobal::instrumented(|| Err("down".to_string()), "stats-flaky");
+        static PLAIN: Global<u8> = Global::new(|| 0);
+
+        // 未开启统计
+        assert_eq!(PLAIN.stats(), None);
+
+        let before = CONFIG.stats().unwrap();
+        assert_eq!(before.initialized_at, None);
+        assert_eq!(before.accesses, 0);
+
+        // 记录执行初始化的线程
+        thread::Builder::new()
+            .name("stats-init".to_string())
+            .spawn(|| CONFIG.get().len())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(CONFIG.get(), "config");
+        let stats = CONFIG.stats().unwrap();
+        assert_eq!(stats.init_thread.as_deref(), Some("stats-init"));
+        assert!(stats.initialized_at.is_some());
+        assert!(stats.init_duration.is_some());
+        assert_eq!(stats.accesses, 2);
+
+        for _ in 0..3 {
+            COUNTER.update(|count| *count += 1);
+        }
+        assert_eq!(COUNTER.stats().unwrap().accesses, 3);
+
+        // 失败的初始化不会被记录为已初始化
+        assert!(FLAKY.get().is_err());
+        let flaky = FLAKY.stats().unwrap();
+        assert_eq!(flaky.initialized_at, None);
+        assert_eq!(flaky.accesses, 1);
+
+        // 方案2始终开启统计
+        let _guard = Singleton2::isolate();
+        drop(Singleton2::get_instance());
+        assert!(Singleton2::stats().accesses >= 1);
+
+        // 其他方案同样开启了统计，其他测试可能已经访问过它们
+        Singleton1::with(|_| ());
+        Singleton3::get_instance();
+        Singleton4::get_instance();
+        drop(Singleton5::read());
+        Singleton6::with(|_| ());
+        for stats in [
+            Singleton1::stats(),
+            Singleton3::stats(),
+            Singleton4::stats(),
+            Singleton5::stats(),
+            Singleton6::stats(),
+        ] {
+            assert!(stats.initialized_at.is_some(), "{:?}", stats);
+            assert!(stats.accesses >= 1, "{:?}", stats);
+        }
+
+        // 其他容器的instrumented构造函数
+        static MODE: Eager<&str> = Eager::instrumented("default", "stats-mode");
+        static BUFFER: PerThread<Vec<u8>> = PerThread::instrumented(Vec::new, "stats-buffer");
+        static ROUTES: HotSwap<Vec<&str>> = HotSwap::instrumented(Vec::new, "stats-routes");
+        static REMOTE: AsyncGlobal<u8> =
+            AsyncGlobal::instrumented(|| Box::pin(async { 7 }), "stats-remote");
+
+        assert_eq!(MODE.init("configured"), Ok(&"configured"));
+        assert_eq!(*MODE.get(), "configured");
+        let mode = MODE.stats().unwrap();
+        assert!(mode.initialized_at.is_some());
+        assert_eq!(mode.accesses, 1);
+
+        BUFFER.with(|_| ());
+        thread::spawn(|| BUFFER.with(|_| ())).join().unwrap();
+        let buffer = BUFFER.stats().unwrap();
+        assert!(buffer.initialized_at.is_some());
+        assert_eq!(buffer.accesses, 2);
+
+        // store发布第一个值时记录为初始化，之后的读写都计入访问次数
+        ROUTES.store(vec!["/"]);
+        assert_eq!(ROUTES.load().len(), 1);
+        assert_eq!(ROUTES.load().len(), 1);
+        let routes = ROUTES.stats().unwrap();
+        assert!(routes.initialized_at.is_some());
+        assert_eq!(routes.accesses, 3);
+
+        assert_eq!(REMOTE.stats().unwrap().initialized_at, None);
+        assert_eq!(executor::block_on(REMOTE.get()), &7);
+        let remote = REMOTE.stats().unwrap();
+        assert!(remote.init_duration.is_some());
+        assert_eq!(remote.accesses, 1);
+
+        // 进程级列表包含所有开启了统计的单例
+        let names: Vec<_> = all_stats().iter().map(|stats| stats.name).collect();
+        for name in [
+            "stats-config",
+            "stats-counter",
+            "stats-flaky",
+            "stats-mode",
+            "stats-buffer",
+            "stats-routes",
+            "stats-remote",
+            "Singleton1",
+            "Singleton2",
+            "Singleton3",
+            "Singleton4",
+            "Singleton5",
+            "Singleton6",
+        ] {
+            assert!(names.contains(&name), "{} missing from {:?}", name, names);
+        }
+        let dump = dump_stats();
+        assert!(dump.contains("stats-config: initialized at "));
+        assert!(dump.contains("by thread `stats-init`"));
+        assert!(dump.contains("stats-flaky: not initialized, 1 accesses"));
+    }
+
+    // 测试初始化环检测: 初始化闭包直接访问自己
+    #[test]
+    fn test_init_cycle_direct() {
+        static DIRECT: Global<Option<AccessError>> =
+            Global::instrumented(|| DIRECT.try_get().err(), "direct");
+        static FALLIBLE: TryGlobal<u8, String> = TryGlobal::new(|| {
+            let inner = FALLIBLE.get().copied();
+            assert_eq!(
+                inner,
+                Err(TryInitError::Access(AccessError::Cycle(vec!["u8", "u8"])))
+            );
+            inner.map_err(|err| err.to_string())
+        });
+
+        let err = DIRECT.get().clone().unwrap();
+        assert_eq!(err, AccessError::Cycle(vec!["direct", "direct"]));
+        assert_eq!(
+            err.to_string(),
+            "initialization cycle detected: direct -> direct"
+        );
+
+        // TryGlobal在环上返回TryInitError::Access，这里初始化闭包把它转换为自己的错误
+        assert_eq!(
+            FALLIBLE.get(),
+            Err(TryInitError::Init(
+                "initialization cycle detected: u8 -> u8".to_string()
+            ))
+        );
+        assert!(!FALLIBLE.is_initialized());
+        assert_eq!(
+            FALLIBLE.get().unwrap_err().to_string(),
+            "initialization failed: initialization cycle detected: u8 -> u8"
+        );
+    }
+
+    // 测试初始化环检测: 经由其他单例间接访问自己，依赖链列出经过的所有单例
+    #[test]
+    fn test_init_cycle_indirect() {
+        static CONFIG: Global<String> =
+            Global::instrumented(|| format!("config({})", LOGGER.get()), "config");
+        static LOGGER: Global<String> =
+            Global::instrumented(|| format!("logger({})", DATABASE.get()), "logger");
+        static DATABASE: Global<String> = Global::instrumented(
+            || match CONFIG.try_get() {
+                Ok(config) => config.clone(),
+                Err(err) => err.to_string(),
+            },
+            "database",
+        );
+
+        // 环被打断后各单例正常完成初始化
+        assert_eq!(
+            CONFIG.get().as_str(),
+            "config(logger(initialization cycle detected: config -> logger -> database -> config))"
+        );
+        assert_eq!(
+            DATABASE.try_get().map(String::as_str),
+            Ok("initialization cycle detected: config -> logger -> database -> config")
+        );
+        assert!(LOGGER.is_initialized());
+    }
+
+    // 测试初始化环检测: GlobalMutex的初始化闭包访问自己时返回错误而不是死锁
+    #[test]
+    fn test_init_cycle_global_mutex() {
+        static QUEUE: GlobalMutex<Vec<String>> = GlobalMutex::instrumented(
+            || match QUEUE.try_lock() {
+                Ok(_) => vec!["unreachable".to_string()],
+                Err(err) => vec![err.to_string()],
+            },
+            "queue",
+        );
+
+        assert_eq!(
+            QUEUE.get(),
+            ["initialization cycle detected: queue -> queue"]
+        );
+        assert!(QUEUE.try_lock().is_ok());
+        assert_eq!(
+            LockError::from(AccessError::Cycle(vec!["queue", "queue"])),
+            LockError::Access(AccessError::Cycle(vec!["queue", "queue"]))
+        );
+    }
+
+    // 测试初始化环检测: PerThread的初始化闭包访问自己时返回错误而不是无限递归
+    #[test]
+    fn test_init_cycle_per_thread() {
+        static SCRATCH: PerThread<Option<AccessError>> =
+            PerThread::instrumented(|| SCRATCH.try_get().err(), "scratch");
+
+        assert_eq!(
+            *SCRATCH.get(),
+            Some(AccessError::Cycle(vec!["scratch", "scratch"]))
+        );
+        // 当前线程的实例只创建一次
+        assert!(Arc::ptr_eq(&SCRATCH.get(), &SCRATCH.get()));
+    }
+
+    // 测试初始化环检测: 注册表中类型的初始化闭包再次获取同一类型时返回错误而不是死锁
+    #[test]
+    fn test_init_cycle_registry() {
+        #[derive(Debug)]
+        struct Service(Option<AccessError>);
+
+        let registry = Registry::new();
+        let service =
+            registry.get_or_init(|| Service(registry.try_get_or_init(|| Service(None)).err()));
+        assert!(matches!(
+            &service.0,
+            Some(AccessError::Cycle(chain)) if chain.len() == 2 && chain[0].ends_with("Service")
+        ));
+        assert!(Arc::ptr_eq(&service, &registry.get::<Service>().unwrap()));
+    }
+
+    // 测试全局配置: 解析、环境变量覆盖、类型化读取和默认值
+    #[test]
+    fn test_config_parse_and_overlay() {
+        let text = r#"
+            # 应用配置
+            name = "demo \"app\""
+            port = 8080   # 行尾注释
+            debug = false
+
+            [db]
+            url = postgres://localhost/app
+            max_conn = 16
+        "#;
+        let mut config = Config::parse(text).unwrap();
+        assert_eq!(config.get_str("name"), Some("demo \"app\""));
+        assert_eq!(config.get::<u16>("port"), Ok(Some(8080)));
+        assert_eq!(config.get_str("db.url"), Some("postgres://localhost/app"));
+
+        config.overlay_env(
+            "APP_",
+            [
+                ("APP_DEBUG", "true"),
+                ("APP_DB__MAX_CONN", "32"),
+                ("OTHER_PORT", "1"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string())),
+        );
+        assert_eq!(config.get_or("debug", false), Ok(true));
+        assert_eq!(config.require::<u32>("db.max_conn"), Ok(32));
+        assert_eq!(config.get_or("db.timeout", 30u64), Ok(30));
+        assert_eq!(config.get::<u16>("missing"), Ok(None));
+        assert_eq!(config.len(), 5);
+
+        // 无法解析的值返回错误而不是默认值
+        assert_eq!(
+            config.get_or::<u16>("db.url", 0),
+            Err(ConfigError::Invalid {
+                key: "db.url".to_string(),
+                value: "postgres://localhost/app".to_string(),
+                expected: "u16",
+            })
+        );
+        assert_eq!(
+            config.validate(&["name", "db.password", "cache.size"]),
+            Err(ConfigError::Missing(vec![
+                "db.password".to_string(),
+                "cache.size".to_string()
+            ]))
+        );
+
+        // 格式错误带行号
+        assert_eq!(
+            Config::parse("a = 1\n[db\n"),
+            Err(ConfigError::Parse {
+                line: 2,
+                message: "unterminated section header".to_string()
+            })
+        );
+        assert!(matches!(
+            Config::parse("key = \"open"),
+            Err(ConfigError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            Config::parse("no equals sign"),
+            Err(ConfigError::Parse { line: 1, .. })
+        ));
+    }
+
+    // 测试全局配置: 从文件加载并安装为全局单例，只能初始化一次
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_config_init_from_file() {
+        use std::path::Path;
+
+        let missing = Config::load(&ConfigSource {
+            file: Some(Path::new("/nonexistent/singleton.conf")),
+            ..Default::default()
+        });
+        assert!(matches!(missing, Err(ConfigError::Io { .. })));
+
+        let path =
+            std::env::temp_dir().join(format!("singleton-config-{}.conf", std::process::id()));
+        std::fs::write(&path, "[server]\nhost = example.com\n").unwrap();
+        let source = ConfigSource {
+            file: Some(&path),
+            env_prefix: Some("SINGLETON_CONFIG_TEST_"),
+            required: &["server.host", "server.port"],
+        };
+
+        // 校验失败时不安装全局配置
+        assert_eq!(
+            Config::init(&source).err(),
+            Some(ConfigError::Missing(vec!["server.port".to_string()]))
+        );
+        assert!(Config::get_instance().is_none());
+
+        std::fs::write(&path, "[server]\nhost = example.com\nport = 443\n").unwrap();
+        let config = Config::init(&source).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            config.require::<String>("server.host").unwrap(),
+            "example.com"
+        );
+        assert!(std::ptr::eq(config, Config::get_instance().unwrap()));
+        assert_eq!(
+            Config::init(&source).err(),
+            Some(ConfigError::AlreadyInitialized)
+        );
+    }
+
+    // 测试对象池用的模拟连接
+    #[derive(Debug)]
+    struct FakeConn {
+        id: usize,
+        healthy: bool,
+    }
+
+    fn fake_conn_pool(config: PoolConfig) -> Pool<FakeConn> {
+        use std::sync::atomic::AtomicUsize;
+
+        let next_id = AtomicUsize::new(0);
+        Pool::new(config, move || {
+            Ok::<_, String>(FakeConn {
+                id: next_id.fetch_add(1, Ordering::SeqCst),
+                healthy: true,
+            })
+        })
+        .unwrap()
+        .with_health_check(|conn: &mut FakeConn| conn.healthy)
+    }
+
+    // 测试对象池: 预创建、借出归还复用、健康检查丢弃坏连接并补足min_size
+    #[test]
+    fn test_pool_checkout_and_health_check() {
+        use std::panic::{self, AssertUnwindSafe};
+        use std::time::Duration;
+
+        let pool = fake_conn_pool(PoolConfig {
+            min_size: 2,
+            max_size: 3,
+            ..Default::default()
+        });
+        assert_eq!(pool.stats().idle, 2);
+        assert_eq!(pool.stats().created, 2);
+
+        let first = pool.checkout().unwrap();
+        let id = first.id;
+        assert_eq!(pool.stats().in_use, 1);
+        drop(first);
+        // 归还的对象被复用
+        assert_eq!(pool.checkout().unwrap().id, id);
+
+        let mut broken = pool.checkout().unwrap();
+        let broken_id = broken.id;
+        broken.healthy = false;
+        drop(broken);
+        // 丢弃后对象总数低于min_size，立即补充一个新连接
+        let stats = pool.stats();
+        assert_eq!((stats.in_use, stats.idle, stats.discarded), (0, 2, 1));
+        assert_eq!(stats.created, 3);
+
+        // 名额被释放，可以创建新连接
+        let conns: Vec<_> = (0..3).map(|_| pool.checkout().unwrap()).collect();
+        assert!(conns.iter().all(|conn| conn.id != broken_id));
+        assert_eq!(pool.stats().created, 4);
+        drop(conns);
+
+        // 健康检查panic时视为未通过，名额不会丢失
+        let panicky = fake_conn_pool(PoolConfig {
+            max_size: 1,
+            ..Default::default()
+        })
+        .with_health_check(|conn: &mut FakeConn| {
+            assert!(conn.healthy, "connection reset");
+            true
+        });
+        let mut conn = panicky.checkout().unwrap();
+        conn.healthy = false;
+        drop(conn);
+        let stats = panicky.stats();
+        assert_eq!((stats.in_use, stats.idle, stats.discarded), (0, 0, 1));
+        assert!(panicky.checkout().unwrap().healthy);
+
+        // 工厂闭包panic时名额被释放，之后的checkout可以重新创建
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+        let flaky = Pool::new(
+            PoolConfig {
+                max_size: 1,
+                checkout_timeout: Duration::from_millis(20),
+                ..Default::default()
+            },
+            move || {
+                assert!(counter.fetch_add(1, Ordering::SeqCst) > 0, "connect failed");
+                Ok::<_, String>(FakeConn {
+                    id: 0,
+                    healthy: true,
+                })
+            },
+        )
+        .unwrap();
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| flaky.checkout().map(|_| ()))).is_err());
+        assert_eq!(flaky.checkout().unwrap().id, 0);
+        assert_eq!(flaky.stats().timeouts, 0);
+
+        // 工厂闭包失败时返回错误
+        let failing = Pool::<FakeConn>::new(PoolConfig::default(), || Err("refused"));
+        let failing = failing.unwrap();
+        assert_eq!(
+            failing.checkout().unwrap_err(),
+            PoolError::Create("refused".to_string())
+        );
+        assert!(
+            Pool::<FakeConn>::new(
+                PoolConfig {
+                    min_size: 1,
+                    ..Default::default()
+                },
+                || Err("refused")
+            )
+            .is_err()
+        );
+    }
+
+    // 测试对象池: 安装为全局单例，池满时等待归还或超时
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_pool_global_wait_and_timeout() {
+        use std::thread;
+        use std::time::Duration;
+
+        static POOL: Global<Pool<FakeConn>> = Global::new(|| {
+            fake_conn_pool(PoolConfig {
+                min_size: 0,
+                max_size: 2,
+                checkout_timeout: Duration::from_secs(10),
+            })
+        });
+
+        // Global自身的stats()是单例的访问统计，通过get()访问对象池
+        let pool = POOL.get();
+        let held: Vec<_> = (0..2).map(|_| pool.checkout().unwrap()).collect();
+        assert_eq!(
+            pool.checkout_timeout(Duration::from_millis(20))
+                .unwrap_err(),
+            PoolError::Timeout(Duration::from_millis(20))
+        );
+
+        // 其他线程归还之后，等待中的checkout成功
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            drop(held);
+        });
+        // 超出时钟范围的超时表示一直等待，不会panic
+        let conn = pool.checkout_timeout(Duration::MAX).unwrap();
+        releaser.join().unwrap();
+
+        let stats = pool.stats();
+        assert_eq!(stats.in_use, 1);
+        assert_eq!(stats.idle, 1);
+        assert_eq!(stats.timeouts, 1);
+        assert!(stats.waits >= 1);
+        assert_eq!(stats.created, 2);
+        drop(conn);
+        assert_eq!(pool.stats().idle, 2);
+    }
+
+    // 测试日志: 按模块路径过滤级别，结构化字段和单行文本格式
+    #[test]
+    fn test_logger_module_levels_and_fields() {
+        let buffer = Arc::new(RingBufferSink::new(3));
+        let logger = Logger::new(Level::Warn)
+            .with_module_level("app::db", Level::Debug)
+            .with_module_level("app::db::pool", Level::Error)
+            .with_sink(buffer.clone());
+
+        assert!(logger.enabled(Level::Debug, "app::db"));
+        assert!(logger.enabled(Level::Debug, "app::db::query"));
+        assert!(!logger.enabled(Level::Warn, "app::db::pool"));
+        // 前缀必须在模块边界上匹配
+        assert!(!logger.enabled(Level::Debug, "app::dbx"));
+        assert!(!logger.enabled(Level::Info, "app"));
+
+        // 同一模块重复设置时后设置的级别生效
+        let overridden = Logger::new(Level::Warn)
+            .with_module_level("app::db", Level::Debug)
+            .with_module_level("app::db", Level::Error);
+        assert!(!overridden.enabled(Level::Warn, "app::db"));
+        assert!(overridden.enabled(Level::Error, "app::db::query"));
+
+        logger.log(Level::Info, "app", format_args!("filtered"), &[]);
+        logger.log(
+            Level::Debug,
+            "app::db::query",
+            format_args!("slow query {}", 1),
+            &[("rows", &3), ("sql", &"SELECT 1")],
+        );
+        let records = buffer.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].level, Level::Debug);
+        assert_eq!(records[0].message, "slow query 1");
+        assert_eq!(records[0].field("rows"), Some("3"));
+        assert!(
+            records[0]
+                .to_string()
+                .ends_with(r#" DEBUG app::db::query: slow query 1 rows=3 sql="SELECT 1""#)
+        );
+
+        // 环形缓冲只保留最近的日志
+        for i in 0..5 {
+            logger.log(Level::Error, "app", format_args!("error {}", i), &[]);
+        }
+        let messages: Vec<_> = buffer.records().into_iter().map(|r| r.message).collect();
+        assert_eq!(messages, ["error 2", "error 3", "error 4"]);
+    }
+
+    // 测试日志: 滚动文件输出，关闭时刷新缓冲
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_logger_rotating_file_and_flush() {
+        let dir = std::env::temp_dir().join(format!("singleton-logger-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        let file = Arc::new(RotatingFileSink::new(&path, 120, 2).unwrap());
+        let logger: &'static Logger =
+            Box::leak(Box::new(Logger::new(Level::Info).with_sink(file.clone())));
+
+        let lifecycle = Lifecycle::new();
+        logger.flush_on_shutdown(&lifecycle).unwrap();
+        for i in 0..10 {
+            logger.log(Level::Info, "app", format_args!("line {}", i), &[("n", &i)]);
+        }
+        // 关闭时刷新，缓冲中的日志写入文件
+        assert_eq!(lifecycle.shutdown(), ["logger"]);
+
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert!(current.ends_with("app: line 9 n=9\n"));
+        assert!(current.len() <= 120);
+        let rotated = std::fs::read_to_string(dir.join("app.log.1")).unwrap();
+        assert!(!rotated.is_empty());
+        assert!(dir.join("app.log.2").exists());
+        // 最多保留keep个旧文件
+        assert!(!dir.join("app.log.3").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 测试日志: 安装为进程级单例，通过log!宏记录并在测试中查询
+    #[test]
+    fn test_logger_install_global() {
+        let buffer = Arc::new(RingBufferSink::new(16));
+        let installed = Logger::new(Level::Info)
+            .with_sink(buffer.clone())
+            .install()
+            .unwrap();
+        assert!(std::ptr::eq(installed, Logger::get_instance().unwrap()));
+        assert!(matches!(
+            Logger::new(Level::Trace).install(),
+            Err(InitError::AlreadyInitialized)
+        ));
+
+        crate::log!(Level::Debug, "not recorded");
+        crate::log!(Level::Warn, user = "bob", attempt = 3; "login failed for {}", "bob");
+        let records = buffer.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].target, "singleton::tests");
+        assert_eq!(records[0].message, "login failed for bob");
+        assert_eq!(
+            records[0].fields,
+            [
+                ("user".to_string(), "bob".to_string()),
+                ("attempt".to_string(), "3".to_string())
+            ]
+        );
+    }
+
+    // 测试缓存: 容量上限按LRU淘汰，命中统计和显式失效
+    #[test]
+    fn test_cache_lru_and_invalidation() {
+        let cache: Cache<&str, u32> = Cache::new(CacheConfig {
+            capacity: 2,
+            ..Default::default()
+        });
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        // 访问a之后，b成为最久未使用的条目
+        assert_eq!(cache.get(&"a"), Some(1));
+        cache.insert("c", 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(3));
+        assert_eq!(cache.get_or_insert_with("b", || 20), 20);
+        assert_eq!(cache.get(&"a"), None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 3));
+        assert_eq!((stats.evictions, stats.len, stats.capacity), (2, 2, 2));
+
+        assert_eq!(cache.invalidate(&"c"), Some(3));
+        assert_eq!(cache.invalidate(&"c"), None);
+        cache.invalidate_all();
+        assert!(cache.is_empty());
+    }
+
+    // 测试缓存: 条目过期时间
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_cache_ttl() {
+        use std::thread;
+        use std::time::Duration;
+
+        let cache: Cache<u32, String> = Cache::new(CacheConfig {
+            capacity: 10,
+            ttl: Some(Duration::from_millis(200)),
+        });
+        cache.insert(1, "short".to_string());
+        cache.insert_with_ttl(2, "long".to_string(), Duration::from_secs(60));
+        cache.insert(3, "short".to_string());
+        // 超出时钟范围的存活时间视为不过期，不会panic
+        cache.insert_with_ttl(4, "forever".to_string(), Duration::MAX);
+        assert_eq!(cache.get(&1).as_deref(), Some("short"));
+
+        thread::sleep(Duration::from_millis(250));
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2).as_deref(), Some("long"));
+        assert_eq!(cache.get(&4).as_deref(), Some("forever"));
+        cache.purge_expired();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().expirations, 2);
+    }
+
+    // 测试缓存管理器: 按名称管理类型化的缓存，多线程并发访问
+    #[test]
+    fn test_cache_manager() {
+        use std::thread;
+
+        let manager = CacheManager::new();
+        let users = manager
+            .cache::<u64, String>("users", CacheConfig::default())
+            .unwrap();
+        users.insert(7, "alice".to_string());
+        // 同名缓存返回同一个实例
+        let again = manager.cache::<u64, String>("users", CacheConfig::default());
+        assert_eq!(again.unwrap().get(&7).as_deref(), Some("alice"));
+        assert!(matches!(
+            manager.get::<String, String>("users"),
+            Err(CacheError::TypeMismatch { .. })
+        ));
+        assert_eq!(
+            manager.get::<u64, String>("sessions").unwrap_err(),
+            CacheError::NotFound("sessions".to_string())
+        );
+        manager.invalidate_all();
+        assert!(users.is_empty());
+        assert!(manager.remove("users"));
+        assert!(manager.names().is_empty());
+
+        // 全局缓存管理器，多线程共享同一个缓存
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(|| {
+                    let squares = CacheManager::global()
+                        .cache::<u32, u32>("test-squares", CacheConfig::default())
+                        .unwrap();
+                    for i in 0..100 {
+                        assert_eq!(squares.get_or_insert_with(i, || i * i), i * i);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let (_, stats) = CacheManager::global()
+            .stats()
+            .into_iter()
+            .find(|(name, _)| name == "test-squares")
+            .unwrap();
+        assert_eq!(stats.hits + stats.misses, 800);
+        assert!(stats.misses >= 100);
+        assert_eq!(stats.len, 100);
+    }
+
+    // 测试原子序列: 多线程并发取号不重复、不遗漏
+    #[test]
+    fn test_sequence() {
+        use std::collections::HashSet;
+        use std::thread;
+
+        static ORDER_IDS: Sequence = Sequence::new(1000);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| thread::spawn(|| (0..10_000).map(|_| ORDER_IDS.next()).collect::<Vec<_>>()))
+            .collect();
+        let mut all = HashSet::new();
+        for handle in handles {
+            let ids = handle.join().unwrap();
+            // 同一线程取到的号单调递增
+            assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+            all.extend(ids);
+        }
+        assert_eq!(all.len(), 80_000);
+        assert_eq!(all.iter().min(), Some(&1000));
+        assert_eq!(all.iter().max(), Some(&80_999));
+        assert_eq!(ORDER_IDS.peek(), 81_000);
+    }
+
+    // 测试Snowflake: 高并发下ID全局唯一且每个线程内递增，全局生成器只能配置一次
+    #[test]
+    fn test_snowflake_unique() {
+        use std::collections::HashSet;
+        use std::thread;
+
+        assert_eq!(
+            Snowflake::new(SnowflakeConfig {
+                node_id: 1024,
+                ..Default::default()
+            })
+            .unwrap_err(),
+            IdError::InvalidNodeId(1024)
+        );
+
+        let generator = Snowflake::init(SnowflakeConfig {
+            node_id: 42,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            Snowflake::init(SnowflakeConfig::default()).unwrap_err(),
+            IdError::AlreadyInitialized
+        );
+        assert_eq!(Snowflake::get_instance().unwrap().node_id(), 42);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(|| {
+                    let generator = Snowflake::get_instance().unwrap();
+                    (0..50_000)
+                        .map(|_| generator.next_id().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut all = HashSet::new();
+        for handle in handles {
+            let ids = handle.join().unwrap();
+            assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+            all.extend(ids);
+        }
+        assert_eq!(all.len(), 400_000);
+
+        let id = generator.next_id().unwrap();
+        let parts = generator.decompose(id);
+        assert_eq!(parts.node_id, 42);
+        assert!(parts.timestamp_ms.abs_diff(system_clock_ms()) < 1000);
+    }
+
+    // 测试Snowflake的时钟处理: 小幅回拨沿用上次时间戳，大幅回拨报错，序号用尽时借用下一毫秒
+    #[test]
+    fn test_snowflake_clock_regression() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::time::Duration;
+
+        static NOW: AtomicU64 = AtomicU64::new(1_000_000);
+
+        let generator = Snowflake::new(SnowflakeConfig {
+            node_id: 3,
+            epoch_ms: 0,
+            max_clock_skew: Duration::from_millis(10),
+            clock: || NOW.load(Ordering::SeqCst),
+        })
+        .unwrap();
+        let first = generator.next_id().unwrap();
+        assert_eq!(
+            generator.decompose(first),
+            SnowflakeParts {
+                timestamp_ms: 1_000_000,
+                node_id: 3,
+                sequence: 0
+            }
+        );
+
+        // 回拨5ms，在容忍范围内: 沿用上次的时间戳，ID仍然递增
+        NOW.store(999_995, Ordering::SeqCst);
+        let second = generator.next_id().unwrap();
+        assert!(second > first);
+        assert_eq!(generator.decompose(second).timestamp_ms, 1_000_000);
+        assert_eq!(generator.decompose(second).sequence, 1);
+
+        // 回拨100ms，超出容忍范围
+        NOW.store(999_900, Ordering::SeqCst);
+        assert_eq!(
+            generator.next_id().unwrap_err(),
+            IdError::ClockMovedBackwards(Duration::from_millis(100))
+        );
+
+        // 时钟恢复后继续生成
+        NOW.store(1_000_001, Ordering::SeqCst);
+        let third = generator.next_id().unwrap();
+        assert!(third > second);
+        assert_eq!(generator.decompose(third).timestamp_ms, 1_000_001);
+
+        // 同一毫秒内生成超过4096个ID时借用下一毫秒
+        let ids: Vec<u64> = (0..4096).map(|_| generator.next_id().unwrap()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        let last = generator.decompose(*ids.last().unwrap());
+        assert_eq!(last.timestamp_ms, 1_000_002);
+        assert_eq!(last.sequence, 0);
+    }
+
+    // 测试多例容器: 每个键只初始化一次，列出已创建的键，淘汰空闲的键
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_multiton() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread;
+        use std::time::Duration;
+
+        static INITS: AtomicUsize = AtomicUsize::new(0);
+        static CLIENTS: Multiton<String, String> = Multiton::new(|tenant| {
+            INITS.fetch_add(1, Ordering::SeqCst);
+            // 放大并发初始化的窗口
+            thread::sleep(Duration::from_millis(20));
+            format!("client for {}", tenant)
+        });
+
+        let handles: Vec<_> = (0..16)
+            .map(|i| {
+                thread::spawn(move || {
+                    let tenant = format!("tenant-{}", i % 4);
+                    CLIENTS.get(&tenant)
+                })
+            })
+            .collect();
+        let clients: Vec<Arc<String>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(INITS.load(Ordering::SeqCst), 4);
+        // 同一个键得到同一个实例
+        assert!(Arc::ptr_eq(&clients[0], &clients[4]));
+        assert_eq!(*clients[1], "client for tenant-1");
+        assert_eq!(
+            CLIENTS.keys(),
+            vec!["tenant-0", "tenant-1", "tenant-2", "tenant-3"]
+        );
+        assert!(CLIENTS.get_if_present(&"tenant-9".to_string()).is_none());
+        assert!(!CLIENTS.contains(&"tenant-9".to_string()));
+
+        // 只访问tenant-0，其余的键空闲后被淘汰
+        thread::sleep(Duration::from_millis(60));
+        CLIENTS.get(&"tenant-0".to_string());
+        let mut evicted = CLIENTS.evict_idle(Duration::from_millis(40));
+        evicted.sort();
+        assert_eq!(evicted, vec!["tenant-1", "tenant-2", "tenant-3"]);
+        assert_eq!(CLIENTS.keys(), vec!["tenant-0"]);
+        // 已取得的实例在淘汰后仍然有效，再次访问会重新创建
+        assert_eq!(*clients[1], "client for tenant-1");
+        let recreated = CLIENTS.get(&"tenant-1".to_string());
+        assert!(!Arc::ptr_eq(&recreated, &clients[1]));
+        assert_eq!(INITS.load(Ordering::SeqCst), 5);
+
+        assert!(CLIENTS.remove(&"tenant-0".to_string()).is_some());
+        // clear移除所有实例，包括刚刚访问过的
+        CLIENTS.get(&"tenant-2".to_string());
+        CLIENTS.clear();
+        assert!(CLIENTS.is_empty());
+        assert!(CLIENTS.keys().is_empty());
+    }
+
+    // 测试作用域单例: 嵌套作用域最内层生效，作用域外与其他线程回退到全局实例，异步任务中的绑定
+    #[test]
+    fn test_scoped() {
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+        use std::thread;
+
+        static TENANT: Scoped<String> = Scoped::new(|| "global".to_string());
+
+        assert_eq!(*TENANT.get(), "global");
+        assert!(!TENANT.is_scoped());
+        {
+            let _outer = TENANT.scope("tenant-a".to_string());
+            assert_eq!(*TENANT.get(), "tenant-a");
+            TENANT.with("tenant-b".to_string(), || {
+                assert_eq!(*TENANT.get(), "tenant-b");
+                // 其他线程不受当前线程的作用域影响
+                let other = thread::spawn(|| TENANT.get().to_string()).join().unwrap();
+                assert_eq!(other, "global");
+            });
+            assert_eq!(*TENANT.get(), "tenant-a");
+            assert_eq!(*TENANT.global(), "global");
+        }
+        assert_eq!(*TENANT.get(), "global");
+
+        // 守卫没有按逆序析构时只移除自己的绑定
+        let first = TENANT.scope("first".to_string());
+        let second = TENANT.scope("second".to_string());
+        drop(first);
+        assert_eq!(*TENANT.get(), "second");
+        drop(second);
+        assert!(!TENANT.is_scoped());
+
+        // 第一次poll返回Pending的Future，验证每次poll都重新绑定
+        struct YieldOnce(bool);
+        impl Future for YieldOnce {
+            type Output = ();
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    Poll::Ready(())
+                } else {
+                    self.0 = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+
+        let task = TENANT.scope_future("task".to_string(), async {
+            let before = TENANT.get().to_string();
+            YieldOnce(false).await;
+            let nested = TENANT
+                .scope_future("nested".to_string(), async { TENANT.get().to_string() })
+                .await;
+            (before, TENANT.get().to_string(), nested)
+        });
+        let (before, after, nested) = executor::block_on(task);
+        assert_eq!((before.as_str(), after.as_str()), ("task", "task"));
+        assert_eq!(nested, "nested");
+        assert_eq!(*TENANT.get(), "global");
+    }
+
+    // 测试快照持久化: 原子写入后在新实例中恢复，快照不存在时调用初始化闭包
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_persistent_restore() {
+        use std::thread;
+        use std::time::Duration;
+
+        let dir = std::env::temp_dir().join(format!("singleton-snapshot-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path: &'static str = Box::leak(
+            dir.join("visits.snapshot")
+                .to_string_lossy()
+                .into_owned()
+                .into_boxed_str(),
+        );
+
+        let visits: Persistent<u64> = Persistent::new(path, || 100);
+        assert_eq!(visits.get(), 100);
+        assert_eq!(visits.checkpoint_if_dirty(), Ok(false));
+        visits.update(|count| *count += 5);
+        assert_eq!(visits.checkpoint_if_dirty(), Ok(true));
+        assert!(!dir.join("visits.snapshot.tmp").exists());
+
+        // 模拟重启: 新实例从快照恢复
+        let restarted: Persistent<u64> = Persistent::new(path, || 0);
+        assert_eq!(restarted.get(), 105);
+        assert_eq!(restarted.last_error(), None);
+
+        // 后台自动保存，停止时最后保存一次
+        let auto: Persistent<u64> = Persistent::new(path, || 0);
+        let auto: &'static Persistent<u64> = Box::leak(Box::new(auto));
+        let checkpointer = auto.auto_checkpoint(Duration::from_millis(10));
+        auto.update(|count| *count += 1);
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(Persistent::<u64>::new(path, || 0).get(), 106);
+        auto.update(|count| *count += 1);
+        checkpointer.stop();
+        assert_eq!(Persistent::<u64>::new(path, || 0).get(), 107);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 测试损坏和版本不匹配的快照: 回退到初始化闭包，坏文件改名保留，旧版本可以迁移
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_persistent_corrupt_snapshot() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Totals {
+            count: u64,
+            sum: u64,
+        }
+
+        // 版本2增加了sum字段，版本1只保存count
+        impl Snapshot for Totals {
+            const VERSION: u32 = 2;
+
+            fn encode(&self) -> Vec<u8> {
+                format!("{},{}", self.count, self.sum).into_bytes()
+            }
+
+            fn decode(data: &[u8]) -> Result<Self, String> {
+                let text = std::str::from_utf8(data).map_err(|err| err.to_string())?;
+                let (count, sum) = text.split_once(',').ok_or("expected `count,sum`")?;
+                Ok(Totals {
+                    count: count.parse().map_err(|_| "invalid count")?,
+                    sum: sum.parse().map_err(|_| "invalid sum")?,
+                })
+            }
+
+            fn migrate(version: u32, data: &[u8]) -> Result<Self, String> {
+                match version {
+                    1 => Ok(Totals {
+                        count: u64::decode(data)?,
+                        sum: 0,
+                    }),
+                    _ => Err(format!("unknown version {}", version)),
+                }
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("singleton-corrupt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let leak = |name: &str| -> &'static str {
+            Box::leak(
+                dir.join(name)
+                    .to_string_lossy()
+                    .into_owned()
+                    .into_boxed_str(),
+            )
+        };
+
+        // 校验和不匹配 (数据被改写)
+        let path = leak("garbled.snapshot");
+        let counter: Persistent<u64> = Persistent::new(path, || 0);
+        counter.update(|count| *count = 42);
+        counter.checkpoint().unwrap();
+        let mut file = std::fs::read(path).unwrap();
+        *file.last_mut().unwrap() = b'3';
+        std::fs::write(path, &file).unwrap();
+        let reloaded: Persistent<u64> = Persistent::new(path, || 7);
+        assert_eq!(reloaded.get(), 7);
+        assert!(matches!(
+            reloaded.last_error(),
+            Some(SnapshotError::Corrupt { reason, .. }) if reason == "checksum mismatch"
+        ));
+        assert!(!std::path::Path::new(path).exists());
+        assert!(dir.join("garbled.snapshot.bad").exists());
+
+        // 不是快照格式的文件
+        let path = leak("junk.snapshot");
+        std::fs::write(path, "not a snapshot").unwrap();
+        let junk: Persistent<u64> = Persistent::new(path, || 1);
+        assert_eq!(junk.get(), 1);
+        assert!(matches!(
+            junk.last_error(),
+            Some(SnapshotError::Corrupt { .. })
+        ));
+
+        // 版本1的快照迁移到版本2
+        let path = leak("totals.snapshot");
+        let old: Persistent<u64> = Persistent::new(path, || 9);
+        old.checkpoint().unwrap();
+        let totals: Persistent<Totals> = Persistent::new(path, || Totals { count: 0, sum: 0 });
+        assert_eq!(totals.get(), Totals { count: 9, sum: 0 });
+        assert_eq!(totals.last_error(), None);
+
+        // 无法迁移的版本
+        totals.checkpoint().unwrap();
+        let downgraded: Persistent<u64> = Persistent::new(path, || 0);
+        assert_eq!(downgraded.get(), 0);
+        assert_eq!(
+            downgraded.last_error(),
+            Some(SnapshotError::VersionMismatch {
+                path: path.to_string(),
+                found: 2,
+                expected: 1,
+            })
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 测试变更通知: 回调收到旧值和新值，回调中读取或修改单例不会死锁，句柄drop后不再通知
+    #[test]
+    fn test_change_notifications() {
+        use std::sync::Mutex;
+        use std::thread;
+
+        let _guard2 = Singleton2::isolate();
+        let _guard5 = Singleton5::isolate();
+
+        // (旧值, 新值, 回调中读到的当前值)
+        let seen2 = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen2);
+        let subscription2 = Singleton2::subscribe(move |old, new| {
+            let current = Singleton2::get_instance().get_data().to_string();
+            recorder
+                .lock()
+                .unwrap()
+                .push((old.to_string(), new.to_string(), current));
+        });
+        Singleton2::get_instance().set_data("first");
+        Singleton2::replace("second");
+        Singleton2::update(|data| data.push('!'));
+        // 值没有变化时不通知
+        Singleton2::replace("second!");
+        assert_eq!(
+            *seen2.lock().unwrap(),
+            vec![
+                (
+                    "Singleton2 instance".to_string(),
+                    "first".to_string(),
+                    "first".to_string()
+                ),
+                (
+                    "first".to_string(),
+                    "second".to_string(),
+                    "second".to_string()
+                ),
+                (
+                    "second".to_string(),
+                    "second!".to_string(),
+                    "second!".to_string()
+                ),
+            ]
+        );
+        drop(subscription2);
+        Singleton2::replace("unobserved");
+        assert_eq!(seen2.lock().unwrap().len(), 3);
+
+        // 回调中修改单例: 新的变更在当前回调返回后按顺序派发
+        let seen5 = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen5);
+        let subscription5 = Singleton5::subscribe(move |old, new| {
+            recorder
+                .lock()
+                .unwrap()
+                .push((old.to_string(), new.to_string()));
+            if new == "request" {
+                Singleton5::replace("response");
+            }
+        });
+        Singleton5::write().set_data("request");
+        assert_eq!(
+            *seen5.lock().unwrap(),
+            vec![
+                ("Singleton5 instance".to_string(), "request".to_string()),
+                ("request".to_string(), "response".to_string()),
+            ]
+        );
+
+        // 通过实例句柄获取的写锁同样在释放时通知
+        Singleton5::get_instance().write().set_data("via handle");
+        assert_eq!(
+            seen5.lock().unwrap().last(),
+            Some(&("response".to_string(), "via handle".to_string()))
+        );
+
+        // 多线程并发修改，每次修改都被通知一次
+        seen5.lock().unwrap().clear();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    for _ in 0..25 {
+                        Singleton5::update(|data| data.push('+'));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let seen5 = seen5.lock().unwrap();
+        assert_eq!(seen5.len(), 100);
+        assert!(seen5.windows(2).all(|pair| pair[0].1 == pair[1].0));
+        subscription5.unsubscribe();
+    }
+
+    // 测试变更通知: 回调panic时报告并继续调用其他回调，之后的事件照常派发
+    #[test]
+    fn test_notifier_listener_panic() {
+        use std::sync::Mutex;
+
+        static CHANGES: Notifier<u32> = Notifier::new("test_notifier");
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen);
+        let _panicking = CHANGES.subscribe(|_, new| assert_ne!(*new, 1, "listener failed"));
+        let _recording =
+            CHANGES.subscribe(move |old, new| recorder.lock().unwrap().push((*old, *new)));
+
+        CHANGES.queue(0, 1);
+        CHANGES.dispatch();
+        CHANGES.queue(1, 2);
+        CHANGES.dispatch();
+        assert_eq!(*seen.lock().unwrap(), vec![(0, 1), (1, 2)]);
+    }
+}
+
+// 自旋锁实现的测试，不依赖std特性: cargo test -p singleton --no-default-features
+// 此时被测试的库按no_std编译，测试代码本身(线程、断言)使用标准库；
+// 库能否在真正没有标准库的目标上编译由check-no-std.sh检查
+#[cfg(test)]
+mod spin_tests {
+    use super::*;
+    // 库按no_std编译时没有标准库的prelude
+    use std::prelude::rust_2024::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::vec;
+
+    // 测试SpinOnce: 并发初始化只执行一次，失败后可以重试，set只成功一次
+    #[test]
+    fn test_spin_once() {
+        static CELL: SpinOnce<usize> = SpinOnce::new();
+        static INITS: AtomicUsize = AtomicUsize::new(0);
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                thread::spawn(move || {
+                    *CELL.get_or_init(|| {
+                        INITS.fetch_add(1, Ordering::SeqCst);
+                        i
+                    })
+                })
+            })
+            .collect();
+        let values: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(INITS.load(Ordering::SeqCst), 1);
+        assert!(values.iter().all(|value| *value == values[0]));
+        assert_eq!(CELL.set(100), Err(100));
+
+        let cell = SpinOnce::new();
+        assert_eq!(cell.get_or_try_init(|| Err("not ready")), Err("not ready"));
+        assert!(!cell.is_initialized());
+        assert_eq!(cell.get_or_try_init(|| Ok::<_, &str>(7)), Ok(&7));
+        assert_eq!(cell.set(8), Err(8));
+        assert_eq!(cell.get(), Some(&7));
+    }
+
+    // 测试自旋锁单例容器: 多线程自增不丢失更新，replace/take返回旧值
+    #[test]
+    fn test_spin_global() {
+        static COUNTER: SpinGlobalMutex<u64> = SpinGlobalMutex::new(|| 0);
+        static NAME: SpinGlobal<&str> = SpinGlobal::new(|| "firmware");
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(|| {
+                    for _ in 0..1000 {
+                        COUNTER.update(|count| *count += 1);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(COUNTER.get(), 8000);
+        assert_eq!(COUNTER.replace(1), 8000);
+        assert_eq!(COUNTER.take(), 1);
+        assert_eq!(*COUNTER.lock(), 0);
+
+        assert!(!NAME.is_initialized());
+        assert_eq!(NAME.len(), 8);
+        assert_eq!(NAME.get_if_initialized(), Some(&"firmware"));
+
+        let mutex = SpinMutex::new(vec![1]);
+        let guard = mutex.lock();
+        assert!(mutex.try_lock().is_none());
+        drop(guard);
+        mutex.lock().push(2);
+        assert_eq!(mutex.into_inner(), vec![1, 2]);
+    }
+
+    // 测试SpinHotSwap: 发布新值后旧快照仍然有效
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_spin_hot_swap() {
+        static SETTINGS: SpinHotSwap<u32> = SpinHotSwap::new(|| 1);
+
+        assert_eq!(SETTINGS.swap(1), None);
+        let old = SETTINGS.load();
+        assert_eq!(*SETTINGS.swap(2).unwrap(), 1);
+        assert_eq!(*old, 1);
+        assert_eq!(*SETTINGS.update(|value| value * 10), 20);
+        assert_eq!(*SETTINGS.load(), 20);
     }
 }
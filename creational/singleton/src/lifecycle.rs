@@ -0,0 +1,176 @@
+// 单例的有序关闭: Lifecycle
+// static中的单例永远不会被drop (方案5的Drop实现从未执行)，需要显式的关闭机制:
+// 单例初始化时登记清理回调，shutdown()按初始化的逆序执行，同时满足声明的依赖关系
+// (依赖方先于被依赖方清理)，关闭之后再访问受管理的单例会返回错误
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+// 访问单例失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessError {
+    // 单例已经被关闭 (或在关闭之后才首次访问)
+    ShutDown(&'static str),
+    // 初始化过程中(直接或经由其他单例)再次访问了正在初始化的单例，
+    // 依次列出依赖链上的单例名称，首尾是同一个单例
+    Cycle(Vec<&'static str>),
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessError::ShutDown(name) => {
+                write!(f, "singleton `{}` was accessed after shutdown", name)
+            }
+            AccessError::Cycle(chain) => {
+                write!(f, "initialization cycle detected: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl Error for AccessError {}
+
+// 受管理单例的清理声明
+// 使用结构体字面量构造，闭包参数的类型可以由Global<T>推导出来
+pub struct Teardown<T> {
+    // 单例名称，用于声明依赖和错误信息
+    pub name: &'static str,
+    // 本单例依赖的其他单例名称，关闭时本单例先于它们清理
+    pub depends_on: &'static [&'static str],
+    // 清理回调
+    pub hook: fn(&T),
+}
+
+// 登记的清理回调
+struct Entry {
+    name: &'static str,
+    depends_on: &'static [&'static str],
+    // 登记顺序即初始化顺序
+    order: u64,
+    teardown: Box<dyn FnOnce() + Send>,
+}
+
+struct State {
+    entries: Vec<Entry>,
+    next_order: u64,
+    shut_down: bool,
+}
+
+pub struct Lifecycle {
+    state: Mutex<State>,
+}
+
+// 进程级的生命周期管理器，Global::with_teardown创建的单例登记在这里
+static GLOBAL_LIFECYCLE: Lifecycle = Lifecycle::new();
+
+impl Lifecycle {
+    // 创建生命周期管理器，可用于static声明
+    pub const fn new() -> Self {
+        Lifecycle {
+            state: Mutex::new(State {
+                entries: Vec::new(),
+                next_order: 0,
+                shut_down: false,
+            }),
+        }
+    }
+
+    // 获取进程级的生命周期管理器
+    pub const fn global() -> &'static Lifecycle {
+        &GLOBAL_LIFECYCLE
+    }
+
+    // 登记清理回调，应在单例初始化完成时调用；已经关闭时返回错误且不登记
+    pub fn register(
+        &self,
+        name: &'static str,
+        depends_on: &'static [&'static str],
+        teardown: impl FnOnce() + Send + 'static,
+    ) -> Result<(), AccessError> {
+        let mut state = self.lock_state();
+        if state.shut_down {
+            return Err(AccessError::ShutDown(name));
+        }
+        let order = state.next_order;
+        state.next_order += 1;
+        state.entries.push(Entry {
+            name,
+            depends_on,
+            order,
+            teardown: Box::new(teardown),
+        });
+        Ok(())
+    }
+
+    // 已登记且尚未清理的单例名称 (按登记顺序)
+    pub fn registered(&self) -> Vec<&'static str> {
+        self.lock_state()
+            .entries
+            .iter()
+            .map(|entry| entry.name)
+            .collect()
+    }
+
+    // 是否已经关闭
+    pub fn is_shut_down(&self) -> bool {
+        self.lock_state().shut_down
+    }
+
+    // 关闭: 按初始化逆序执行清理回调，返回实际执行的顺序
+    // 没有其他未清理单例依赖的单例才能被清理，声明的依赖存在环时退化为纯逆序；
+    // 某个回调panic不会影响其余回调的执行；重复调用不会再执行任何回调
+    pub fn shutdown(&self) -> Vec<&'static str> {
+        let mut remaining = {
+            let mut state = self.lock_state();
+            state.shut_down = true;
+            std::mem::take(&mut state.entries)
+        };
+
+        let mut order = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let needed = |name: &str| {
+                remaining
+                    .iter()
+                    .any(|other| other.depends_on.contains(&name))
+            };
+            let index = (0..remaining.len())
+                .filter(|&i| !needed(remaining[i].name))
+                .max_by_key(|&i| remaining[i].order)
+                .or_else(|| (0..remaining.len()).max_by_key(|&i| remaining[i].order))
+                .unwrap();
+            let entry = remaining.swap_remove(index);
+            // 回调在锁外执行，回调中可以访问尚未清理的单例
+            if panic::catch_unwind(AssertUnwindSafe(entry.teardown)).is_err() {
+                eprintln!("teardown of singleton `{}` panicked", entry.name);
+            }
+            order.push(entry.name);
+        }
+        order
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Lifecycle::new()
+    }
+}
+
+impl fmt::Debug for Lifecycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lifecycle")
+            .field("registered", &self.registered())
+            .field("shut_down", &self.is_shut_down())
+            .finish()
+    }
+}
+
+// 关闭进程级的生命周期管理器，通常在main返回之前调用
+pub fn shutdown() -> Vec<&'static str> {
+    Lifecycle::global().shutdown()
+}
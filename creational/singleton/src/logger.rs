@@ -0,0 +1,401 @@
+// 全局日志单例: Logger
+// 启动时通过Logger::install安装一次，之后任何模块都可以通过log!宏记录日志。
+// 支持按模块路径设置级别、可插拔的输出(标准错误、滚动文件、测试用的内存环形缓冲)、
+// 结构化的键值字段；安装时向生命周期管理器登记刷新回调，shutdown()时保证缓冲的日志落盘
+use crate::eager::InitError;
+use crate::lifecycle::{AccessError, Lifecycle};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 日志级别，越靠前越严重
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        })
+    }
+}
+
+// 一条日志
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub timestamp: SystemTime,
+    pub level: Level,
+    // 产生日志的模块路径
+    pub target: String,
+    pub message: String,
+    // 结构化字段，按记录时的顺序
+    pub fields: Vec<(String, String)>,
+}
+
+impl Record {
+    // 按名称查找字段的值
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// 单行文本格式: 时间戳 级别 模块: 消息 key=value ...
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{}.{:03} {:<5} {}: {}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_millis(),
+            self.level,
+            self.target,
+            self.message
+        )?;
+        for (key, value) in &self.fields {
+            // 含空白、等号或引号的值加引号，保证一行可以被无歧义地解析
+            if value.is_empty()
+                || value.contains(|c: char| c.is_whitespace() || c == '=' || c == '"')
+            {
+                write!(f, " {}={:?}", key, value)?;
+            } else {
+                write!(f, " {}={}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// 日志输出
+pub trait Sink: Send + Sync {
+    // 写入一条日志
+    fn write(&self, record: &Record);
+
+    // 刷新缓冲，关闭时调用
+    fn flush(&self) {}
+}
+
+// 输出到标准错误
+#[derive(Debug, Default)]
+pub struct StderrSink;
+
+impl Sink for StderrSink {
+    fn write(&self, record: &Record) {
+        eprintln!("{}", record);
+    }
+
+    fn flush(&self) {
+        let _ = io::stderr().flush();
+    }
+}
+
+// 滚动文件输出: 当前文件超过max_bytes时依次重命名为path.1、path.2...，最多保留keep个旧文件
+pub struct RotatingFileSink {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: Mutex<(BufWriter<File>, u64)>,
+}
+
+impl RotatingFileSink {
+    // 打开(追加)日志文件
+    pub fn new(path: impl AsRef<Path>, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let (file, size) = Self::open(&path)?;
+        Ok(RotatingFileSink {
+            path,
+            max_bytes,
+            keep,
+            file: Mutex::new((file, size)),
+        })
+    }
+
+    fn open(path: &Path) -> io::Result<(BufWriter<File>, u64)> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok((BufWriter::new(file), size))
+    }
+
+    // 第n个旧文件的路径
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self, current: &mut (BufWriter<File>, u64)) -> io::Result<()> {
+        current.0.flush()?;
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.keep).rev() {
+                let from = self.rotated(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+        }
+        *current = Self::open(&self.path)?;
+        Ok(())
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut current = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        let len = line.len() as u64 + 1;
+        if current.1 > 0 && current.1 + len > self.max_bytes {
+            self.rotate(&mut current)?;
+        }
+        writeln!(current.0, "{}", line)?;
+        current.1 += len;
+        Ok(())
+    }
+}
+
+impl Sink for RotatingFileSink {
+    fn write(&self, record: &Record) {
+        // 写日志失败时不能再通过日志报告，只能输出到标准错误
+        if let Err(err) = self.write_line(&record.to_string()) {
+            eprintln!("cannot write log file `{}`: {}", self.path.display(), err);
+        }
+    }
+
+    fn flush(&self) {
+        let mut current = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = current.0.flush();
+    }
+}
+
+impl fmt::Debug for RotatingFileSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RotatingFileSink")
+            .field("path", &self.path)
+            .field("max_bytes", &self.max_bytes)
+            .field("keep", &self.keep)
+            .finish()
+    }
+}
+
+// 内存环形缓冲输出，保留最近的capacity条日志，测试中用来断言记录的日志
+#[derive(Debug)]
+pub struct RingBufferSink {
+    capacity: usize,
+    records: Mutex<VecDeque<Record>>,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        RingBufferSink {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    // 缓冲中的日志 (从旧到新)
+    pub fn records(&self) -> Vec<Record> {
+        self.lock_records().iter().cloned().collect()
+    }
+
+    // 清空缓冲
+    pub fn clear(&self) {
+        self.lock_records().clear();
+    }
+
+    fn lock_records(&self) -> MutexGuard<'_, VecDeque<Record>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Sink for RingBufferSink {
+    fn write(&self, record: &Record) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut records = self.lock_records();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record.clone());
+    }
+}
+
+pub struct Logger {
+    // 没有匹配的模块级别时使用的级别
+    default_level: Level,
+    // 按模块路径前缀设置的级别，最长的匹配前缀生效
+    module_levels: Vec<(String, Level)>,
+    sinks: Vec<Arc<dyn Sink>>,
+}
+
+// 进程级的日志单例
+static GLOBAL_LOGGER: OnceLock<Logger> = OnceLock::new();
+
+impl Logger {
+    // 创建日志器，默认记录default_level及更严重的日志，没有任何输出
+    pub fn new(default_level: Level) -> Self {
+        Logger {
+            default_level,
+            module_levels: Vec::new(),
+            sinks: Vec::new(),
+        }
+    }
+
+    // 为模块路径(及其子模块)设置级别，例如"app::db"同时作用于"app::db::pool"
+    // 同一模块重复设置时后设置的级别生效
+    pub fn with_module_level(mut self, module: &str, level: Level) -> Self {
+        match self
+            .module_levels
+            .iter_mut()
+            .find(|(existing, _)| existing == module)
+        {
+            Some((_, existing)) => *existing = level,
+            None => self.module_levels.push((module.to_string(), level)),
+        }
+        // 长的前缀优先匹配
+        self.module_levels
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        self
+    }
+
+    // 添加输出，保留Arc的克隆即可在测试中查询输出的内容
+    pub fn with_sink(mut self, sink: Arc<dyn Sink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    // 安装为进程级的日志单例，只能调用一次
+    // 同时登记关闭回调，shutdown()时刷新所有输出
+    pub fn install(self) -> Result<&'static Logger, InitError> {
+        let mut logger = Some(self);
+        let installed = GLOBAL_LOGGER.get_or_init(|| logger.take().unwrap());
+        if logger.is_some() {
+            return Err(InitError::AlreadyInitialized);
+        }
+        // 已经关闭时无需登记，日志仍会写入但不会自动刷新
+        let _ = installed.flush_on_shutdown(Lifecycle::global());
+        Ok(installed)
+    }
+
+    // 获取进程级的日志单例，尚未安装时返回None
+    pub fn get_instance() -> Option<&'static Logger> {
+        GLOBAL_LOGGER.get()
+    }
+
+    // 在生命周期管理器中登记刷新回调
+    // 日志器通常最先安装，按初始化逆序关闭时最后刷新，其他单例清理时记录的日志也不会丢失
+    pub fn flush_on_shutdown(&'static self, lifecycle: &Lifecycle) -> Result<(), AccessError> {
+        lifecycle.register("logger", &[], move || self.flush())
+    }
+
+    // 模块target的level级别日志是否会被记录
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        let threshold = self
+            .module_levels
+            .iter()
+            .find(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default_level, |(_, level)| *level);
+        level <= threshold
+    }
+
+    // 记录一条日志
+    pub fn log(
+        &self,
+        level: Level,
+        target: &str,
+        message: fmt::Arguments<'_>,
+        fields: &[(&str, &dyn fmt::Display)],
+    ) {
+        if !self.enabled(level, target) || self.sinks.is_empty() {
+            return;
+        }
+        let record = Record {
+            timestamp: SystemTime::now(),
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+        for sink in &self.sinks {
+            sink.write(&record);
+        }
+    }
+
+    // 刷新所有输出
+    pub fn flush(&self) {
+        for sink in &self.sinks {
+            sink.flush();
+        }
+    }
+}
+
+impl fmt::Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger")
+            .field("default_level", &self.default_level)
+            .field("module_levels", &self.module_levels)
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+// 通过进程级的日志单例记录一条日志，尚未安装时丢弃，供log!宏使用
+pub fn log_global(
+    level: Level,
+    target: &str,
+    message: fmt::Arguments<'_>,
+    fields: &[(&str, &dyn fmt::Display)],
+) {
+    if let Some(logger) = Logger::get_instance() {
+        logger.log(level, target, message, fields);
+    }
+}
+
+// 通过进程级的日志单例记录日志，模块路径取自调用处
+// log!(Level::Info, "listening on {}", addr);
+// log!(Level::Warn, user = name, attempt = 3; "login failed");
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($key:ident = $value:expr),+ ; $($arg:tt)+) => {
+        $crate::log_global(
+            $level,
+            ::std::module_path!(),
+            ::std::format_args!($($arg)+),
+            &[$((::std::stringify!($key), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log_global(
+            $level,
+            ::std::module_path!(),
+            ::std::format_args!($($arg)+),
+            &[],
+        )
+    };
+}
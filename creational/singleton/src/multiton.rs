@@ -0,0 +1,176 @@
+// 多例容器: Multiton<K, V>
+// 单例的推广: 每个键一个实例，例如每个租户一个客户端。
+// 首次访问某个键时调用初始化闭包创建实例，同一个键并发访问时只初始化一次，
+// 不同键的初始化互不阻塞；长时间未访问的键可以被淘汰，淘汰后再次访问会重新创建
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+// 一个键对应的实例
+struct Slot<V> {
+    value: OnceLock<Arc<V>>,
+    created: Instant,
+    // 最近一次访问距created的纳秒数
+    last_access: AtomicU64,
+}
+
+impl<V> Slot<V> {
+    fn new() -> Self {
+        Slot {
+            value: OnceLock::new(),
+            created: Instant::now(),
+            last_access: AtomicU64::new(0),
+        }
+    }
+
+    fn touch(&self) {
+        let elapsed = self.created.elapsed().as_nanos() as u64;
+        self.last_access.fetch_max(elapsed, Ordering::Relaxed);
+    }
+
+    fn idle_for(&self) -> Duration {
+        let last_access = Duration::from_nanos(self.last_access.load(Ordering::Relaxed));
+        self.created.elapsed().saturating_sub(last_access)
+    }
+}
+
+pub struct Multiton<K, V, F = fn(&K) -> V> {
+    slots: Mutex<BTreeMap<K, Arc<Slot<V>>>>,
+    init: F,
+}
+
+impl<K, V, F> Multiton<K, V, F> {
+    // 创建多例容器，可用于static声明
+    pub const fn new(init: F) -> Self {
+        Multiton {
+            slots: Mutex::new(BTreeMap::new()),
+            init,
+        }
+    }
+}
+
+impl<K, V, F> Multiton<K, V, F>
+where
+    K: Ord + Clone,
+    F: Fn(&K) -> V,
+{
+    // 获取键对应的实例，不存在时创建
+    // 初始化闭包在容器锁外执行，同一个键的其他访问者等待这次初始化完成
+    pub fn get(&self, key: &K) -> Arc<V> {
+        let slot = {
+            let mut slots = self.lock_slots();
+            match slots.get(key) {
+                Some(slot) => Arc::clone(slot),
+                None => {
+                    let slot = Arc::new(Slot::new());
+                    slots.insert(key.clone(), Arc::clone(&slot));
+                    slot
+                }
+            }
+        };
+        slot.touch();
+        Arc::clone(slot.value.get_or_init(|| Arc::new((self.init)(key))))
+    }
+
+    // 获取已经创建的实例，不存在时返回None而不创建
+    pub fn get_if_present(&self, key: &K) -> Option<Arc<V>> {
+        let slot = Arc::clone(self.lock_slots().get(key)?);
+        let value = Arc::clone(slot.value.get()?);
+        slot.touch();
+        Some(value)
+    }
+
+    // 键对应的实例是否已经创建
+    pub fn contains(&self, key: &K) -> bool {
+        self.lock_slots()
+            .get(key)
+            .is_some_and(|slot| slot.value.get().is_some())
+    }
+
+    // 已经创建实例的键 (按键排序)
+    pub fn keys(&self) -> Vec<K> {
+        self.lock_slots()
+            .iter()
+            .filter(|(_, slot)| slot.value.get().is_some())
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    // 已经创建的实例数
+    pub fn len(&self) -> usize {
+        self.lock_slots()
+            .values()
+            .filter(|slot| slot.value.get().is_some())
+            .count()
+    }
+
+    // 是否没有任何已创建的实例
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 移除键对应的实例，已经取得的Arc仍然有效，下次访问会重新创建
+    pub fn remove(&self, key: &K) -> Option<Arc<V>> {
+        let mut slots = self.lock_slots();
+        let value = Arc::clone(slots.get(key)?.value.get()?);
+        slots.remove(key);
+        Some(value)
+    }
+
+    // 淘汰超过max_idle未被访问的实例，返回被淘汰的键
+    // 正在初始化的键不会被淘汰，保证同一个键不会同时存在两次初始化
+    pub fn evict_idle(&self, max_idle: Duration) -> Vec<K> {
+        let mut evicted = Vec::new();
+        // 被淘汰的实例在锁外析构
+        let mut dropped = Vec::new();
+        {
+            let mut slots = self.lock_slots();
+            slots.retain(|key, slot| {
+                let idle = slot.value.get().is_some() && slot.idle_for() > max_idle;
+                if idle {
+                    evicted.push(key.clone());
+                    dropped.push(Arc::clone(slot));
+                }
+                !idle
+            });
+        }
+        drop(dropped);
+        evicted
+    }
+
+    // 移除所有已创建的实例，不论最近是否被访问
+    // 与evict_idle相同，正在初始化的键保留，由初始化它的访问者完成
+    pub fn clear(&self) {
+        let mut dropped = Vec::new();
+        {
+            let mut slots = self.lock_slots();
+            slots.retain(|_, slot| {
+                let created = slot.value.get().is_some();
+                if created {
+                    dropped.push(Arc::clone(slot));
+                }
+                !created
+            });
+        }
+        // 被移除的实例在锁外析构
+        drop(dropped);
+    }
+
+    fn lock_slots(&self) -> MutexGuard<'_, BTreeMap<K, Arc<Slot<V>>>> {
+        self.slots.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<K: fmt::Debug, V, F> fmt::Debug for Multiton<K, V, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slots = self.slots.lock().unwrap_or_else(PoisonError::into_inner);
+        let keys: Vec<&K> = slots
+            .iter()
+            .filter(|(_, slot)| slot.value.get().is_some())
+            .map(|(key, _)| key)
+            .collect();
+        f.debug_struct("Multiton").field("keys", &keys).finish()
+    }
+}
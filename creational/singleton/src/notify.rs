@@ -0,0 +1,186 @@
+// 可变单例的变更通知: Notifier<T>
+// 单例数据被修改时，其他组件通过subscribe登记的回调收到(旧值, 新值)。
+// 修改发生在单例的锁内，此时只把事件放入队列；释放锁之后再调用回调，
+// 因此回调中可以再次读取甚至修改单例而不会死锁。
+// 同一时间只有一个线程在派发，其他线程放入的事件由它按修改顺序一并派发。
+// 派发通常发生在守卫的drop中，回调panic时只报告，不影响其他回调和之后的事件
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+type Listener<T> = Arc<dyn Fn(&T, &T) + Send + Sync>;
+
+pub struct Notifier<T> {
+    // 被通知的单例名称，用于报告回调的panic
+    name: &'static str,
+    listeners: Mutex<Vec<(u64, Listener<T>)>>,
+    // 登记的回调数，没有回调时修改方无需复制旧值
+    listener_count: AtomicUsize,
+    // 尚未派发的(旧值, 新值)
+    pending: Mutex<VecDeque<(T, T)>>,
+    // 是否有线程正在派发
+    dispatching: AtomicBool,
+    next_id: AtomicU64,
+}
+
+impl<T> Notifier<T> {
+    // 创建通知器，可用于static声明
+    pub const fn new(name: &'static str) -> Self {
+        Notifier {
+            name,
+            listeners: Mutex::new(Vec::new()),
+            listener_count: AtomicUsize::new(0),
+            pending: Mutex::new(VecDeque::new()),
+            dispatching: AtomicBool::new(false),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    // 登记回调，返回的句柄被drop时取消登记
+    pub fn subscribe(
+        &'static self,
+        listener: impl Fn(&T, &T) + Send + Sync + 'static,
+    ) -> Subscription
+    where
+        T: Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut listeners = self.lock_listeners();
+        listeners.push((id, Arc::new(listener)));
+        self.listener_count
+            .store(listeners.len(), Ordering::Release);
+        Subscription { notifier: self, id }
+    }
+
+    // 是否有登记的回调
+    pub fn has_listeners(&self) -> bool {
+        self.listener_count.load(Ordering::Acquire) > 0
+    }
+
+    // 记录一次修改，在持有单例的锁时调用，回调要等到dispatch时才执行
+    pub fn queue(&self, old: T, new: T) {
+        self.lock_pending().push_back((old, new));
+    }
+
+    // 派发队列中的事件，必须在释放单例的锁之后调用
+    // 其他线程正在派发(或回调中再次修改单例)时直接返回，事件由正在派发的线程处理
+    pub fn dispatch(&self) {
+        loop {
+            if self
+                .dispatching
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                return;
+            }
+            {
+                // 回调的panic已被捕获，这里防止的是事件本身析构时panic，
+                // 否则派发标记不会被清除，之后的事件不会再被派发
+                let _dispatching = ClearOnDrop(&self.dispatching);
+                loop {
+                    // 先取出事件并释放队列锁，回调中的修改可以继续放入事件
+                    let event = self.lock_pending().pop_front();
+                    let Some((old, new)) = event else {
+                        break;
+                    };
+                    // 复制回调列表后在锁外调用，回调中可以登记或取消登记
+                    let listeners: Vec<Listener<T>> = self
+                        .lock_listeners()
+                        .iter()
+                        .map(|(_, listener)| Arc::clone(listener))
+                        .collect();
+                    for listener in listeners {
+                        if panic::catch_unwind(AssertUnwindSafe(|| listener(&old, &new))).is_err() {
+                            eprintln!("change listener of singleton `{}` panicked", self.name);
+                        }
+                    }
+                }
+            }
+            // 清除标记之前其他线程放入的事件可能无人派发，再检查一次
+            if self.lock_pending().is_empty() {
+                return;
+            }
+        }
+    }
+
+    fn unsubscribe(&self, id: u64) {
+        let removed = {
+            let mut listeners = self.lock_listeners();
+            let removed = listeners
+                .iter()
+                .position(|(listener_id, _)| *listener_id == id)
+                .map(|index| listeners.remove(index));
+            self.listener_count
+                .store(listeners.len(), Ordering::Release);
+            removed
+        };
+        // 回调捕获的数据在锁外析构
+        drop(removed);
+    }
+
+    fn lock_listeners(&self) -> MutexGuard<'_, Vec<(u64, Listener<T>)>> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_pending(&self) -> MutexGuard<'_, VecDeque<(T, T)>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> fmt::Debug for Notifier<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifier")
+            .field("name", &self.name)
+            .field("listeners", &self.listener_count.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+struct ClearOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for ClearOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+// 取消登记时只需要编号，不需要知道通知器的值类型
+trait Unsubscribe: Sync {
+    fn unsubscribe(&self, id: u64);
+}
+
+impl<T: Send> Unsubscribe for Notifier<T> {
+    fn unsubscribe(&self, id: u64) {
+        Notifier::unsubscribe(self, id);
+    }
+}
+
+// 回调的登记句柄，drop时取消登记
+#[must_use = "dropping the subscription unsubscribes immediately"]
+pub struct Subscription {
+    notifier: &'static dyn Unsubscribe,
+    id: u64,
+}
+
+impl Subscription {
+    // 取消登记 (与drop相同，使意图更明确)
+    pub fn unsubscribe(self) {}
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.notifier.unsubscribe(self.id);
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish()
+    }
+}
@@ -0,0 +1,201 @@
+// 线程级单例容器: PerThread<T>
+// 特点: 每个线程在首次访问时创建自己的实例，线程退出时执行可选的析构钩子，
+// 并且可以从任意线程遍历/汇总所有仍然存活的线程实例 (例如汇总各线程的计数器)
+use crate::cycle;
+use crate::lifecycle::AccessError;
+use crate::stats::{Instrument, SingletonStats};
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, ThreadId};
+
+pub struct PerThread<T, F = fn() -> T> {
+    init: F,
+    // 线程退出时对该线程实例执行的钩子
+    on_exit: Option<fn(&T)>,
+    // 所有存活线程的实例
+    live: Mutex<Vec<(ThreadId, Arc<T>)>>,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+}
+
+// 线程本地存储中的一个实例，线程退出时随线程本地存储一起析构
+struct Slot {
+    // 所属PerThread的地址
+    owner: usize,
+    value: Arc<dyn Any + Send + Sync>,
+    // 从所属PerThread的存活列表中移除并执行析构钩子
+    retire: Option<Box<dyn FnOnce()>>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if let Some(retire) = self.retire.take() {
+            retire();
+        }
+    }
+}
+
+thread_local! {
+    // 当前线程所有PerThread实例，不同的PerThread通过地址区分
+    static SLOTS: RefCell<Vec<Slot>> = const { RefCell::new(Vec::new()) };
+}
+
+impl<T, F> PerThread<T, F> {
+    // 创建线程级单例容器，可用于static声明
+    pub const fn new(init: F) -> Self {
+        Self::build(init, None)
+    }
+
+    // 创建开启统计的线程级单例容器: 记录所有线程的访问次数，
+    // 初始化信息为第一个线程实例的创建时间、耗时和线程
+    pub const fn instrumented(init: F, name: &'static str) -> Self {
+        Self::build(init, Some(Instrument::new(name)))
+    }
+
+    // 创建带析构钩子的线程级单例容器，线程退出时对该线程的实例执行钩子
+    // 注意: 主线程退出时进程直接结束，线程本地存储不一定析构，钩子可能不会执行
+    pub const fn with_exit_hook(init: F, hook: fn(&T)) -> Self {
+        Self::new(init).on_exit(hook)
+    }
+
+    // 设置线程退出时的析构钩子，可与instrumented组合用于static声明
+    pub const fn on_exit(mut self, hook: fn(&T)) -> Self {
+        self.on_exit = Some(hook);
+        self
+    }
+
+    const fn build(init: F, instrument: Option<Instrument>) -> Self {
+        PerThread {
+            init,
+            on_exit: None,
+            live: Mutex::new(Vec::new()),
+            instrument,
+        }
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 所有存活线程实例的快照
+    pub fn snapshot(&self) -> Vec<Arc<T>> {
+        self.lock_live()
+            .iter()
+            .map(|(_, value)| Arc::clone(value))
+            .collect()
+    }
+
+    // 存活线程实例的数量
+    pub fn live_count(&self) -> usize {
+        self.lock_live().len()
+    }
+
+    // 遍历所有存活线程的实例
+    // 闭包在锁外执行，闭包中可以安全地访问本容器
+    pub fn for_each(&self, mut f: impl FnMut(&T)) {
+        for value in self.snapshot() {
+            f(&value);
+        }
+    }
+
+    // 汇总所有存活线程的实例
+    pub fn fold<B>(&self, init: B, mut f: impl FnMut(B, &T) -> B) -> B {
+        self.snapshot()
+            .iter()
+            .fold(init, |acc, value| f(acc, value))
+    }
+
+    fn name(&self) -> &'static str {
+        match &self.instrument {
+            Some(instrument) => instrument.name(),
+            None => std::any::type_name::<T>(),
+        }
+    }
+
+    fn lock_live(&self) -> MutexGuard<'_, Vec<(ThreadId, Arc<T>)>> {
+        self.live.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, F> PerThread<T, F>
+where
+    T: Send + Sync + 'static,
+    F: Fn() -> T + Sync + 'static,
+{
+    // 访问当前线程的实例，首次访问时创建
+    pub fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.get())
+    }
+
+    // 获取当前线程的实例
+    // 初始化存在环时panic，需要处理这种场景请使用try_get
+    pub fn get(&'static self) -> Arc<T> {
+        self.try_get().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // 获取当前线程的实例，初始化闭包中(直接或经由其他单例)再次访问本容器时
+    // 返回AccessError::Cycle，而不是无限递归地创建实例
+    pub fn try_get(&'static self) -> Result<Arc<T>, AccessError> {
+        if let Some(instrument) = &self.instrument {
+            instrument.record_access();
+        }
+        let owner = self as *const Self as usize;
+        let cached = SLOTS.try_with(|slots| {
+            slots
+                .borrow()
+                .iter()
+                .find(|slot| slot.owner == owner)
+                .map(|slot| Arc::clone(&slot.value))
+        });
+        match cached {
+            // 地址相同的槽位一定属于本容器，类型必然匹配
+            Ok(Some(value)) => return Ok(value.downcast::<T>().expect("slot type mismatch")),
+            Ok(None) => {}
+            // 线程本地存储正在析构 (例如在其他线程本地变量的析构函数中访问)，
+            // 此时返回一个临时实例，不计入存活列表
+            Err(_) => return Ok(Arc::new((self.init)())),
+        }
+
+        // 初始化闭包在借用线程本地存储之外执行，闭包中可以访问其他PerThread
+        let _init = cycle::enter(self, self.name())?;
+        let value = Arc::new(match &self.instrument {
+            Some(instrument) => instrument.record_init(&self.init),
+            None => (self.init)(),
+        });
+        let id = thread::current().id();
+        let registered = SLOTS.try_with(|slots| {
+            slots.borrow_mut().push(Slot {
+                owner,
+                value: Arc::clone(&value) as Arc<dyn Any + Send + Sync>,
+                retire: Some(Box::new(move || self.retire(id))),
+            })
+        });
+        if registered.is_ok() {
+            self.lock_live().push((id, Arc::clone(&value)));
+        }
+        Ok(value)
+    }
+
+    fn retire(&self, id: ThreadId) {
+        let retired = {
+            let mut live = self.lock_live();
+            live.iter()
+                .position(|(owner, _)| *owner == id)
+                .map(|index| live.swap_remove(index).1)
+        };
+        if let (Some(value), Some(hook)) = (retired, self.on_exit) {
+            hook(&value);
+        }
+    }
+}
+
+impl<T, F> fmt::Debug for PerThread<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PerThread")
+            .field("live_count", &self.live_count())
+            .finish()
+    }
+}
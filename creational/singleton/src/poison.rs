@@ -0,0 +1,78 @@
+// 互斥锁中毒策略
+// 持有锁的线程panic后Mutex会被标记为中毒，直接unwrap会让之后的每次访问都panic，
+// 这里提供三种处理方式供基于Mutex的单例选择
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoisonPolicy {
+    // 返回PoisonedError，由调用方决定如何处理
+    Propagate,
+    // 忽略中毒，继续使用panic时留下的数据 (默认)
+    Recover,
+    // 丢弃panic时留下的数据，使用初始化函数重新创建实例
+    Reinitialize,
+}
+
+impl PoisonPolicy {
+    // 默认策略，const上下文(容器的构造函数)中无法调用Default::default，使用此常量
+    pub(crate) const DEFAULT: PoisonPolicy = PoisonPolicy::Recover;
+
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => PoisonPolicy::Recover,
+            2 => PoisonPolicy::Reinitialize,
+            _ => PoisonPolicy::Propagate,
+        }
+    }
+
+    const fn as_u8(self) -> u8 {
+        match self {
+            PoisonPolicy::Propagate => 0,
+            PoisonPolicy::Recover => 1,
+            PoisonPolicy::Reinitialize => 2,
+        }
+    }
+}
+
+impl Default for PoisonPolicy {
+    fn default() -> Self {
+        PoisonPolicy::DEFAULT
+    }
+}
+
+// 可在static中使用的中毒策略存储
+pub(crate) struct AtomicPoisonPolicy(AtomicU8);
+
+impl AtomicPoisonPolicy {
+    pub(crate) const fn new(policy: PoisonPolicy) -> Self {
+        AtomicPoisonPolicy(AtomicU8::new(policy.as_u8()))
+    }
+
+    pub(crate) fn load(&self) -> PoisonPolicy {
+        PoisonPolicy::from_u8(self.0.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn store(&self, policy: PoisonPolicy) {
+        self.0.store(policy.as_u8(), Ordering::Release);
+    }
+}
+
+// 单例的互斥锁已中毒 (之前持有锁的线程panic了)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoisonedError {
+    pub name: &'static str,
+}
+
+impl fmt::Display for PoisonedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "singleton `{}` is poisoned: a thread panicked while holding its lock",
+            self.name
+        )
+    }
+}
+
+impl Error for PoisonedError {}
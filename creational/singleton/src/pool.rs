@@ -0,0 +1,321 @@
+// 对象池: Pool<T>
+// 数据库连接池是单例的典型用途: 整个进程共享一个池，池中的对象按需创建、用完归还。
+// 配合Global安装为全局单例: static POOL: Global<Pool<Conn>> = Global::new(|| ...);
+// 借出的对象由RAII守卫持有，守卫析构时执行健康检查并归还，池满时借出会等待直到超时
+use std::error::Error;
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+// 对象池的配置，使用结构体字面量构造，未指定的字段用..Default::default()补齐
+#[derive(Debug, Clone, Copy)]
+pub struct PoolConfig {
+    // 对象总数的下限: 创建池时预先创建，健康检查丢弃对象后补足
+    pub min_size: usize,
+    // 对象总数(空闲 + 借出)的上限
+    pub max_size: usize,
+    // 池满时checkout等待的最长时间，Duration::MAX等超出时钟范围的值表示一直等待
+    pub checkout_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            min_size: 0,
+            max_size: 10,
+            checkout_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+// 借出对象失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolError {
+    // 等待超时，池中的对象都已借出且已达到上限
+    Timeout(Duration),
+    // 工厂闭包创建对象失败
+    Create(String),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Timeout(waited) => {
+                write!(
+                    f,
+                    "timed out after {:?} waiting for a pooled object",
+                    waited
+                )
+            }
+            PoolError::Create(message) => write!(f, "failed to create pooled object: {}", message),
+        }
+    }
+}
+
+impl Error for PoolError {}
+
+// 对象池的统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    // 借出中的对象数
+    pub in_use: usize,
+    // 空闲的对象数
+    pub idle: usize,
+    // 需要等待其他线程归还的checkout次数
+    pub waits: u64,
+    // 等待超时的checkout次数
+    pub timeouts: u64,
+    // 工厂闭包创建的对象总数
+    pub created: u64,
+    // 归还时未通过健康检查而被丢弃的对象数
+    pub discarded: u64,
+}
+
+struct State<T> {
+    idle: Vec<T>,
+    // 借出中的对象数
+    in_use: usize,
+    // 正在由工厂闭包创建的对象数，计入上限
+    creating: usize,
+    stats: PoolStats,
+}
+
+type Factory<T> = Box<dyn Fn() -> Result<T, String> + Send + Sync>;
+type HealthCheck<T> = Box<dyn Fn(&mut T) -> bool + Send + Sync>;
+
+pub struct Pool<T> {
+    config: PoolConfig,
+    factory: Factory<T>,
+    health_check: Option<HealthCheck<T>>,
+    state: Mutex<State<T>>,
+    // 有对象归还或名额释放时通知等待的线程
+    available: Condvar,
+}
+
+impl<T> Pool<T> {
+    // 创建对象池并预先创建min_size个对象，创建失败时返回错误
+    pub fn new<E: fmt::Display>(
+        config: PoolConfig,
+        factory: impl Fn() -> Result<T, E> + Send + Sync + 'static,
+    ) -> Result<Self, PoolError> {
+        let factory: Factory<T> = Box::new(move || factory().map_err(|err| err.to_string()));
+        let mut idle = Vec::with_capacity(config.max_size);
+        for _ in 0..config.min_size.min(config.max_size) {
+            idle.push(factory().map_err(PoolError::Create)?);
+        }
+        let created = idle.len() as u64;
+        Ok(Pool {
+            config,
+            factory,
+            health_check: None,
+            state: Mutex::new(State {
+                idle,
+                in_use: 0,
+                creating: 0,
+                stats: PoolStats {
+                    created,
+                    ..PoolStats::default()
+                },
+            }),
+            available: Condvar::new(),
+        })
+    }
+
+    // 设置归还时的健康检查，返回false的对象会被丢弃而不是放回池中
+    pub fn with_health_check(
+        mut self,
+        check: impl Fn(&mut T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.health_check = Some(Box::new(check));
+        self
+    }
+
+    // 借出一个对象，池满时最多等待配置的checkout_timeout
+    pub fn checkout(&self) -> Result<Pooled<'_, T>, PoolError> {
+        self.checkout_timeout(self.config.checkout_timeout)
+    }
+
+    // 借出一个对象，池满时最多等待timeout
+    pub fn checkout_timeout(&self, timeout: Duration) -> Result<Pooled<'_, T>, PoolError> {
+        // 超出时钟范围的超时没有截止时间
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.lock_state();
+        let mut waited = false;
+        loop {
+            if let Some(value) = state.idle.pop() {
+                state.in_use += 1;
+                return Ok(self.pooled(value));
+            }
+            if state.idle.len() + state.in_use + state.creating < self.config.max_size {
+                return self.create(state);
+            }
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                state.stats.timeouts += 1;
+                return Err(PoolError::Timeout(timeout));
+            }
+            if !waited {
+                waited = true;
+                state.stats.waits += 1;
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    self.available
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .available
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+
+    // 统计快照
+    pub fn stats(&self) -> PoolStats {
+        let state = self.lock_state();
+        PoolStats {
+            in_use: state.in_use,
+            idle: state.idle.len(),
+            ..state.stats
+        }
+    }
+
+    // 在锁外调用工厂闭包创建对象，创建期间占用一个名额
+    // 工厂闭包panic时先释放名额再继续传播panic，否则这个名额会永久丢失
+    fn create(&self, mut state: MutexGuard<'_, State<T>>) -> Result<Pooled<'_, T>, PoolError> {
+        state.creating += 1;
+        drop(state);
+        let created = panic::catch_unwind(AssertUnwindSafe(|| (self.factory)()));
+        let mut state = self.lock_state();
+        state.creating -= 1;
+        match created {
+            Ok(Ok(value)) => {
+                state.in_use += 1;
+                state.stats.created += 1;
+                Ok(self.pooled(value))
+            }
+            Ok(Err(message)) => {
+                // 释放的名额让给其他等待的线程
+                self.available.notify_one();
+                Err(PoolError::Create(message))
+            }
+            Err(payload) => {
+                drop(state);
+                self.available.notify_one();
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+
+    fn pooled(&self, value: T) -> Pooled<'_, T> {
+        Pooled {
+            pool: self,
+            value: ManuallyDrop::new(value),
+        }
+    }
+
+    // 归还对象: 在锁外执行健康检查，未通过时丢弃对象并释放名额，
+    // 对象总数因此低于min_size时补充一个新对象
+    // 健康检查panic时视为未通过，保证名额被释放
+    fn give_back(&self, mut value: T) {
+        let healthy = match &self.health_check {
+            Some(check) => panic::catch_unwind(AssertUnwindSafe(|| check(&mut value)))
+                .unwrap_or_else(|_| {
+                    eprintln!("health check of pooled object panicked");
+                    false
+                }),
+            None => true,
+        };
+        let (discarded, replenish) = {
+            let mut state = self.lock_state();
+            state.in_use -= 1;
+            if healthy {
+                state.idle.push(value);
+                (None, false)
+            } else {
+                state.stats.discarded += 1;
+                let total = state.idle.len() + state.in_use + state.creating;
+                let replenish = total < self.config.min_size.min(self.config.max_size);
+                if replenish {
+                    state.creating += 1;
+                }
+                (Some(value), replenish)
+            }
+        };
+        if !replenish {
+            self.available.notify_one();
+        }
+        if replenish {
+            self.replenish();
+        }
+        // 被丢弃的对象在锁外析构
+        drop(discarded);
+    }
+
+    // 补充一个空闲对象，调用方已占用一个创建名额
+    // 在析构路径上执行，工厂闭包失败或panic时放弃补充，之后的checkout会按需创建
+    fn replenish(&self) {
+        let created = panic::catch_unwind(AssertUnwindSafe(|| (self.factory)()));
+        let mut state = self.lock_state();
+        state.creating -= 1;
+        if let Ok(Ok(value)) = created {
+            state.idle.push(value);
+            state.stats.created += 1;
+        }
+        drop(state);
+        self.available.notify_one();
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("config", &self.config)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+// 借出的对象，析构时归还给对象池
+pub struct Pooled<'a, T> {
+    pool: &'a Pool<T>,
+    value: ManuallyDrop<T>,
+}
+
+impl<T> Deref for Pooled<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Pooled<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        // value只在这里被取出一次
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        self.pool.give_back(value);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Pooled<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pooled").field(&*self.value).finish()
+    }
+}
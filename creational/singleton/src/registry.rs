@@ -0,0 +1,138 @@
+// 按类型索引的服务注册表: Registry
+// 特点: 每种类型最多保存一个实例，替代为每个全局对象单独声明static (如INSTANCE2/INSTANCE3)，
+// 即服务定位器风格的单例；线程安全，可以查询已注册的类型
+use crate::cycle;
+use crate::lifecycle::AccessError;
+use std::any::{Any, TypeId, type_name};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+type Slot = Arc<OnceLock<Arc<dyn Any + Send + Sync>>>;
+
+pub struct Registry {
+    // 类型 -> (类型名, 实例槽位)
+    // 槽位单独加锁，初始化某个类型时不会阻塞其他类型的访问
+    entries: RwLock<BTreeMap<TypeId, (&'static str, Slot)>>,
+}
+
+// 进程级的全局注册表
+static GLOBAL_REGISTRY: Registry = Registry::new();
+
+impl Registry {
+    // 创建空的注册表，可用于static声明
+    pub const fn new() -> Self {
+        Registry {
+            entries: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    // 获取全局注册表
+    pub fn global() -> &'static Registry {
+        &GLOBAL_REGISTRY
+    }
+
+    // 注册类型T的实例，该类型已有实例时返回Err并交还value
+    pub fn register<T: Any + Send + Sync>(&self, value: T) -> Result<Arc<T>, T> {
+        let mut value = Some(value);
+        let instance = self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(instance),
+            Some(value) => Err(value),
+        }
+    }
+
+    // 获取类型T的实例
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let slot = self.slot_if_present(TypeId::of::<T>())?;
+        slot.get().map(|instance| downcast(instance))
+    }
+
+    // 获取类型T的实例，不存在时调用f创建，并发调用时f只执行一次
+    // 初始化存在环时panic，需要处理这种场景请使用try_get_or_init
+    pub fn get_or_init<T: Any + Send + Sync>(&self, f: impl FnOnce() -> T) -> Arc<T> {
+        self.try_get_or_init(f)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // 获取类型T的实例，不存在时调用f创建
+    // f中(直接或经由其他单例)再次获取类型T时返回AccessError::Cycle，而不是在槽位上死锁
+    pub fn try_get_or_init<T: Any + Send + Sync>(
+        &self,
+        f: impl FnOnce() -> T,
+    ) -> Result<Arc<T>, AccessError> {
+        // 初始化在注册表锁之外执行，f中可以访问注册表中的其他类型
+        let slot = self.slot::<T>();
+        if let Some(instance) = slot.get() {
+            return Ok(downcast(instance));
+        }
+        // 以槽位地址区分不同注册表中的同一类型
+        let _init = cycle::enter(&*slot, type_name::<T>())?;
+        let instance = slot.get_or_init(|| Arc::new(f()));
+        Ok(downcast(instance))
+    }
+
+    // 类型T是否已注册
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    // 已注册的类型名 (按类型名排序)
+    pub fn registered_types(&self) -> Vec<&'static str> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        let mut names: Vec<&'static str> = entries
+            .values()
+            .filter(|(_, slot)| slot.get().is_some())
+            .map(|(name, _)| *name)
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    // 已注册的类型数量
+    pub fn len(&self) -> usize {
+        self.registered_types().len()
+    }
+
+    // 是否没有任何已注册的类型
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn slot_if_present(&self, id: TypeId) -> Option<Slot> {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        entries.get(&id).map(|(_, slot)| Arc::clone(slot))
+    }
+
+    fn slot<T: Any>(&self) -> Slot {
+        if let Some(slot) = self.slot_if_present(TypeId::of::<T>()) {
+            return slot;
+        }
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let (_, slot) = entries
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| (type_name::<T>(), Arc::new(OnceLock::new())));
+        Arc::clone(slot)
+    }
+}
+
+// 槽位按TypeId索引，实例类型必然与T一致
+fn downcast<T: Any + Send + Sync>(instance: &Arc<dyn Any + Send + Sync>) -> Arc<T> {
+    Arc::clone(instance)
+        .downcast::<T>()
+        .expect("registry entry type mismatch")
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("types", &self.registered_types())
+            .finish()
+    }
+}
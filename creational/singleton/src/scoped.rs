@@ -0,0 +1,171 @@
+// 作用域单例: Scoped<T>
+// 进程级单例(如方案3)让同一进程中无法运行两个逻辑上独立的"应用"(两个租户、两个测试用例)。
+// Scoped在全局实例之上提供作用域绑定: 在线程或异步任务的某个作用域内绑定一个值，
+// 作用域内的get返回最内层绑定的值，没有绑定时回退到全局实例；作用域可以嵌套
+//
+// 实现: 绑定保存在线程本地的栈中，以(Scoped地址, 绑定编号)区分；
+// 异步任务可能在不同线程上被轮询，因此scope_future在每次poll期间绑定，poll返回后解除
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+
+pub struct Scoped<T, F = fn() -> T> {
+    // 全局实例，首次在作用域外访问时创建
+    global: OnceLock<Arc<T>>,
+    init: F,
+}
+
+// 全局递增的绑定编号，守卫析构时按编号移除自己的绑定
+static NEXT_BINDING: AtomicU64 = AtomicU64::new(1);
+
+type Binding = (usize, u64, Arc<dyn Any + Send + Sync>);
+
+thread_local! {
+    // 当前线程的绑定栈: (Scoped地址, 绑定编号, 绑定的值)，越靠后越内层
+    static BINDINGS: RefCell<Vec<Binding>> = const { RefCell::new(Vec::new()) };
+}
+
+impl<T, F> Scoped<T, F> {
+    // 创建作用域单例，可用于static声明，init在首次需要全局实例时调用
+    pub const fn new(init: F) -> Self {
+        Scoped {
+            global: OnceLock::new(),
+            init,
+        }
+    }
+
+    fn key(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+impl<T: Send + Sync + 'static, F> Scoped<T, F> {
+    // 在当前线程绑定value，守卫析构时解除绑定
+    // 守卫不能跨线程传递；嵌套的绑定中最内层的生效
+    pub fn scope(&self, value: T) -> ScopeGuard<'_, T, F> {
+        self.enter(Arc::new(value))
+    }
+
+    // 在绑定value的作用域内执行f
+    pub fn with<R>(&self, value: T, f: impl FnOnce() -> R) -> R {
+        let _guard = self.scope(value);
+        f()
+    }
+
+    // 在异步任务中绑定value: 返回的Future每次被poll时绑定，因此任务在线程间迁移后仍然有效
+    pub fn scope_future<Fut: Future>(&self, value: T, future: Fut) -> ScopedFuture<'_, T, F, Fut> {
+        ScopedFuture {
+            scoped: self,
+            value: Arc::new(value),
+            future: Box::pin(future),
+        }
+    }
+
+    // 当前线程是否处于本单例的作用域内
+    pub fn is_scoped(&self) -> bool {
+        self.current().is_some()
+    }
+
+    fn enter(&self, value: Arc<T>) -> ScopeGuard<'_, T, F> {
+        let id = NEXT_BINDING.fetch_add(1, Ordering::Relaxed);
+        BINDINGS.with(|bindings| bindings.borrow_mut().push((self.key(), id, value)));
+        ScopeGuard {
+            scoped: self,
+            id,
+            _not_send: PhantomData,
+        }
+    }
+
+    // 最内层的绑定
+    fn current(&self) -> Option<Arc<T>> {
+        let key = self.key();
+        BINDINGS.with(|bindings| {
+            let binding = bindings
+                .borrow()
+                .iter()
+                .rev()
+                .find(|(owner, _, _)| *owner == key)
+                .map(|(_, _, value)| Arc::clone(value))?;
+            binding.downcast::<T>().ok()
+        })
+    }
+}
+
+impl<T: Send + Sync + 'static, F: Fn() -> T> Scoped<T, F> {
+    // 获取实例: 最内层作用域绑定的值，不在作用域内时为全局实例
+    pub fn get(&self) -> Arc<T> {
+        self.current().unwrap_or_else(|| self.global())
+    }
+
+    // 获取全局实例，忽略作用域绑定
+    pub fn global(&self) -> Arc<T> {
+        Arc::clone(self.global.get_or_init(|| Arc::new((self.init)())))
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Scoped<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scoped")
+            .field("global", &self.global.get())
+            .finish()
+    }
+}
+
+// 作用域绑定守卫，析构时解除绑定
+pub struct ScopeGuard<'a, T, F> {
+    scoped: &'a Scoped<T, F>,
+    id: u64,
+    // 绑定保存在当前线程，守卫不能被移动到其他线程析构
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T, F> Drop for ScopeGuard<'_, T, F> {
+    fn drop(&mut self) {
+        let key = self.scoped.key();
+        // 按编号移除，守卫没有按嵌套的逆序析构时也只移除自己的绑定
+        let removed = BINDINGS.try_with(|bindings| {
+            let mut bindings = bindings.borrow_mut();
+            bindings
+                .iter()
+                .rposition(|(owner, id, _)| *owner == key && *id == self.id)
+                .map(|index| bindings.remove(index))
+        });
+        // 绑定的值在借用结束后析构，其析构函数可以再次访问作用域单例
+        drop(removed);
+    }
+}
+
+impl<T, F> fmt::Debug for ScopeGuard<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopeGuard").field("id", &self.id).finish()
+    }
+}
+
+// 在每次poll期间绑定值的Future，由Scoped::scope_future创建
+pub struct ScopedFuture<'a, T, F, Fut> {
+    scoped: &'a Scoped<T, F>,
+    value: Arc<T>,
+    future: Pin<Box<Fut>>,
+}
+
+impl<T: Send + Sync + 'static, F, Fut: Future> Future for ScopedFuture<'_, T, F, Fut> {
+    type Output = Fut::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Fut::Output> {
+        let this = &mut *self;
+        let _guard = this.scoped.enter(Arc::clone(&this.value));
+        this.future.as_mut().poll(cx)
+    }
+}
+
+impl<T, F, Fut> fmt::Debug for ScopedFuture<'_, T, F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedFuture").finish_non_exhaustive()
+    }
+}
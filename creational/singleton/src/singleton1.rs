@@ -1,25 +1,50 @@
-// 单例模式实现方案1: 基本懒汉式 (非线程安全)
-// 注意: 这种实现不是线程安全的，仅用于演示
-#![allow(static_mut_refs)]
+// 单例模式实现方案1: 基本懒汉式
+// 注意: 早期版本使用static mut并返回&'static mut，两次调用会得到互相别名的可变引用(未定义行为)
+// 现在是GlobalMutex的包装: 首次访问时创建实例，通过闭包加锁访问，不再需要unsafe
+use crate::global_mutex::GlobalMutex;
+use crate::stats::SingletonStats;
 
 pub struct Singleton1 {
     data: String,
 }
 
-// 静态变量存储单例实例
-static mut INSTANCE1: Option<Box<Singleton1>> = None;
+// 静态变量存储单例实例，首次访问时调用构造函数创建，并记录初始化与访问统计
+static INSTANCE1: GlobalMutex<Singleton1> = GlobalMutex::instrumented(
+    || Singleton1 {
+        data: "Singleton1 instance".to_string(),
+    },
+    "Singleton1",
+);
 
 impl Singleton1 {
-    // 获取单例实例
-    pub fn get_instance() -> &'static mut Singleton1 {
-        unsafe {
-            if INSTANCE1.is_none() {
-                INSTANCE1 = Some(Box::new(Singleton1 {
-                    data: "Singleton1 instance".to_string(),
-                }));
-            }
-            INSTANCE1.as_mut().unwrap()
-        }
+    // 以只读方式访问单例实例
+    pub fn with<R>(f: impl FnOnce(&Singleton1) -> R) -> R {
+        Self::with_mut(|instance| f(instance))
+    }
+
+    // 以可变方式访问单例实例
+    pub fn with_mut<R>(f: impl FnOnce(&mut Singleton1) -> R) -> R {
+        INSTANCE1.update(f)
+    }
+
+    // 原子地读-改-写数据，返回闭包的结果
+    pub fn update<R>(f: impl FnOnce(&mut String) -> R) -> R {
+        Self::with_mut(|instance| f(&mut instance.data))
+    }
+
+    // 替换数据，返回旧数据
+    pub fn replace(data: &str) -> String {
+        Self::update(|current| std::mem::replace(current, data.to_string()))
+    }
+
+    // 取出数据并留下空字符串
+    pub fn take() -> String {
+        Self::update(std::mem::take)
+    }
+
+    // 初始化与访问统计
+    pub fn stats() -> SingletonStats {
+        INSTANCE1.stats().expect("Singleton1 is instrumented")
     }
 
     // 设置数据
@@ -31,4 +56,4 @@ impl Singleton1 {
     pub fn get_data(&self) -> &str {
         &self.data
     }
-}
\ No newline at end of file
+}
@@ -1,30 +1,178 @@
-// 单例模式实现方案2: 使用lazy_static宏 (线程安全)
-use lazy_static::lazy_static;
-use std::sync::Mutex;
+// 单例模式实现方案2: 互斥锁 + 延迟初始化 (线程安全，可配置锁中毒策略)
+// 早期版本使用lazy_static宏声明Mutex<Singleton2>，现在是GlobalMutex的包装，
+// 在const上下文中构造，不再需要宏和外部依赖
+use crate::global_mutex::{GlobalMutex, LockError};
+use crate::notify::{Notifier, Subscription};
+use crate::poison::{PoisonPolicy, PoisonedError};
+use crate::stats::SingletonStats;
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::MutexGuard;
+
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::IsolationGuard;
+#[cfg(any(test, feature = "testing"))]
+use std::sync::{Mutex, PoisonError};
 
 pub struct Singleton2 {
     data: String,
 }
 
-lazy_static! {
-    static ref INSTANCE2: Mutex<Singleton2> = Mutex::new(Singleton2 {
-        data: "Singleton2 instance".to_string(),
-    });
-}
+// 实例的初始数据
+const DEFAULT_DATA2: &str = "Singleton2 instance";
+
+// 首次访问时初始化，记录初始化与访问统计；锁中毒时默认返回PoisonedError
+static INSTANCE2: GlobalMutex<Singleton2> =
+    GlobalMutex::<Singleton2>::instrumented(Singleton2::new, "Singleton2")
+        .with_poison_policy(PoisonPolicy::Propagate);
+
+// 数据变更通知
+static CHANGES2: Notifier<String> = Notifier::new("Singleton2");
+
+// 测试用的独占锁
+#[cfg(any(test, feature = "testing"))]
+static SERIAL2: Mutex<()> = Mutex::new(());
 
 impl Singleton2 {
+    fn new() -> Singleton2 {
+        Singleton2 {
+            data: DEFAULT_DATA2.to_string(),
+        }
+    }
+
     // 获取单例实例
-    pub fn get_instance() -> std::sync::MutexGuard<'static, Singleton2> {
-        INSTANCE2.lock().unwrap()
+    // 锁中毒且策略为Propagate时panic，需要处理中毒的场景请使用try_get_instance
+    pub fn get_instance() -> Singleton2Guard {
+        Self::try_get_instance().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // 获取单例实例，按中毒策略处理锁中毒
+    pub fn try_get_instance() -> Result<Singleton2Guard, PoisonedError> {
+        match INSTANCE2.try_lock() {
+            Ok(guard) => Ok(Singleton2Guard {
+                guard: ManuallyDrop::new(guard),
+            }),
+            Err(LockError::Poisoned(err)) => Err(err),
+            // Singleton2::new不访问任何单例，初始化不会形成环
+            Err(LockError::Access(err)) => unreachable!("{}", err),
+        }
+    }
+
+    // 初始化与访问统计
+    pub fn stats() -> SingletonStats {
+        INSTANCE2.stats().expect("Singleton2 is instrumented")
+    }
+
+    // 设置锁中毒时的处理策略
+    pub fn set_poison_policy(policy: PoisonPolicy) {
+        INSTANCE2.set_poison_policy(policy);
+    }
+
+    // 获取锁中毒时的处理策略
+    pub fn poison_policy() -> PoisonPolicy {
+        INSTANCE2.poison_policy()
+    }
+
+    // 忽略中毒状态加锁，测试辅助函数使用
+    #[cfg(any(test, feature = "testing"))]
+    fn lock_ignoring_poison() -> MutexGuard<'static, Singleton2> {
+        INSTANCE2.lock_ignoring_poison()
+    }
+
+    // 恢复为初始数据 (仅用于测试)
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reset() {
+        Self::lock_ignoring_poison().data = DEFAULT_DATA2.to_string();
+    }
+
+    // 独占单例并恢复为初始数据，守卫析构时恢复原数据 (仅用于测试)
+    #[cfg(any(test, feature = "testing"))]
+    pub fn isolate() -> IsolationGuard {
+        let serial = SERIAL2.lock().unwrap_or_else(PoisonError::into_inner);
+        let previous = std::mem::replace(
+            &mut Self::lock_ignoring_poison().data,
+            DEFAULT_DATA2.to_string(),
+        );
+        IsolationGuard::new(serial, move || Self::lock_ignoring_poison().data = previous)
+    }
+
+    // 原子地读-改-写数据，返回闭包的结果
+    // 锁中毒的处理与get_instance相同；数据有变化时通知订阅者
+    pub fn update<R>(f: impl FnOnce(&mut String) -> R) -> R {
+        let mut instance = Self::get_instance();
+        if !CHANGES2.has_listeners() {
+            return f(&mut instance.data);
+        }
+        let old = instance.data.clone();
+        let result = f(&mut instance.data);
+        if instance.data != old {
+            CHANGES2.queue(old, instance.data.clone());
+        }
+        result
     }
 
-    // 设置数据
+    // 订阅数据变更: 每次修改成功后以(旧值, 新值)调用listener，返回的句柄被drop时取消订阅
+    // 回调在释放单例的锁之后执行，其中可以再次访问单例
+    pub fn subscribe(listener: impl Fn(&str, &str) + Send + Sync + 'static) -> Subscription {
+        CHANGES2.subscribe(move |old: &String, new: &String| listener(old, new))
+    }
+
+    // 替换数据，返回旧数据
+    pub fn replace(data: &str) -> String {
+        Self::update(|current| std::mem::replace(current, data.to_string()))
+    }
+
+    // 取出数据并留下空字符串
+    pub fn take() -> String {
+        Self::update(std::mem::take)
+    }
+
+    // 设置数据，数据有变化时在守卫释放锁之后通知订阅者
     pub fn set_data(&mut self, data: &str) {
-        self.data = data.to_string();
+        let old = std::mem::replace(&mut self.data, data.to_string());
+        if CHANGES2.has_listeners() && old != self.data {
+            CHANGES2.queue(old, self.data.clone());
+        }
     }
 
     // 获取数据
     pub fn get_data(&self) -> &str {
         &self.data
     }
-}
\ No newline at end of file
+}
+
+// 单例实例的锁守卫，析构时先释放锁，再派发持有锁期间产生的变更通知
+pub struct Singleton2Guard {
+    guard: ManuallyDrop<MutexGuard<'static, Singleton2>>,
+}
+
+impl Deref for Singleton2Guard {
+    type Target = Singleton2;
+
+    fn deref(&self) -> &Singleton2 {
+        &self.guard
+    }
+}
+
+impl DerefMut for Singleton2Guard {
+    fn deref_mut(&mut self) -> &mut Singleton2 {
+        &mut self.guard
+    }
+}
+
+impl Drop for Singleton2Guard {
+    fn drop(&mut self) {
+        // guard只在这里被析构一次
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        CHANGES2.dispatch();
+    }
+}
+
+impl fmt::Debug for Singleton2Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Singleton2Guard")
+            .field(&self.guard.data)
+            .finish()
+    }
+}
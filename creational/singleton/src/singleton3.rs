@@ -1,4 +1,8 @@
 // 单例模式实现方案3: 使用OnceLock (Rust 1.70+推荐方式)
+use crate::lifecycle::AccessError;
+use crate::stats::{Instrument, SingletonStats};
+use crate::try_global::{TryInit, TryInitError};
+use std::fmt::Display;
 use std::sync::OnceLock;
 
 pub struct Singleton3 {
@@ -6,17 +10,68 @@ pub struct Singleton3 {
 }
 
 static INSTANCE3: OnceLock<Singleton3> = OnceLock::new();
+// 可失败初始化使用的锁，同时记录最近一次初始化失败的错误信息
+static TRY_INIT3: TryInit = TryInit::new();
+// 初始化与访问统计
+static STATS3: Instrument = Instrument::new("Singleton3");
 
 impl Singleton3 {
     // 获取单例实例
+    // 初始化存在环时panic并给出依赖链，需要处理的场景请使用try_get
     pub fn get_instance() -> &'static Singleton3 {
-        INSTANCE3.get_or_init(|| Singleton3 {
-            data: "Singleton3 instance".to_string(),
-        })
+        Self::try_get().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // 获取单例实例，初始化过程中再次访问本单例时返回AccessError::Cycle
+    // (OnceLock在这种情况下会死锁或panic)
+    pub fn try_get() -> Result<&'static Singleton3, AccessError> {
+        STATS3.record_access();
+        if let Some(instance) = INSTANCE3.get() {
+            return Ok(instance);
+        }
+        let _init = TRY_INIT3.enter("Singleton3")?;
+        Ok(INSTANCE3.get_or_init(|| {
+            STATS3.record_init(|| Singleton3 {
+                data: "Singleton3 instance".to_string(),
+            })
+        }))
+    }
+
+    // 获取单例实例 (可失败初始化)
+    // 初始化闭包返回错误时实例保持未初始化，之后的调用可以重试
+    // 初始化闭包中(直接或经由其他单例)再次访问本单例时返回TryInitError::Access(AccessError::Cycle)
+    pub fn try_get_instance<F, E>(init: F) -> Result<&'static Singleton3, TryInitError<E>>
+    where
+        F: FnOnce() -> Result<String, E>,
+        E: Display,
+    {
+        STATS3.record_access();
+        TRY_INIT3.run(
+            "Singleton3",
+            || INSTANCE3.get(),
+            || {
+                let timer = STATS3.start_init();
+                let data = init()?;
+                timer.finish();
+                Ok(data)
+            },
+            // 与get_instance并发时以先完成者为准
+            |data| INSTANCE3.get_or_init(|| Singleton3 { data }),
+        )
+    }
+
+    // 初始化与访问统计 (只记录成功的初始化)
+    pub fn stats() -> SingletonStats {
+        STATS3.stats()
+    }
+
+    // 最近一次初始化失败的错误信息
+    pub fn last_error() -> Option<String> {
+        TRY_INIT3.last_error()
     }
 
     // 获取数据
     pub fn get_data(&self) -> &str {
         &self.data
     }
-}
\ No newline at end of file
+}
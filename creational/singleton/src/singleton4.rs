@@ -1,33 +1,42 @@
 // 单例模式实现方案4: 饿汉式 (线程安全)
 // 特点: 程序启动时就初始化，天然线程安全
+// 默认值在编译期确定，main中可以在第一次读取之前调用一次init设置实际数据
+use crate::eager::{Eager, InitError};
+use crate::stats::SingletonStats;
 
 pub struct Singleton4 {
     data: String,
 }
 
 // 静态变量存储单例实例，程序启动时就初始化
-static INSTANCE4: Singleton4 = Singleton4 {
-    data: String::new(),
-};
+static INSTANCE4: Eager<Singleton4> = Eager::instrumented(
+    Singleton4 {
+        data: String::new(),
+    },
+    "Singleton4",
+);
 
 impl Singleton4 {
     // 获取单例实例
     pub fn get_instance() -> &'static Singleton4 {
-        &INSTANCE4
+        INSTANCE4.get()
     }
 
     // 初始化数据
-    pub fn init(data: &str) -> &'static Singleton4 {
-        let _ = data;
-        // 注意: 由于实例是不可变的，这里实际上是返回已初始化的实例
-        // 饿汉式在程序启动时就已初始化，这里的init只是设置数据的一种方式
-        // 但由于实例是不可变的，这种方式在Rust中并不适用
-        // 以下是演示代码，实际使用中应在创建时就设置好数据
-        &INSTANCE4
+    // 只能在第一次get_instance之前调用一次，否则返回错误且数据保持不变
+    pub fn init(data: &str) -> Result<&'static Singleton4, InitError> {
+        INSTANCE4.init(Singleton4 {
+            data: data.to_string(),
+        })
+    }
+
+    // 数据确定的时间与读取统计
+    pub fn stats() -> SingletonStats {
+        INSTANCE4.stats().expect("Singleton4 is instrumented")
     }
 
     // 获取数据
     pub fn get_data(&self) -> &str {
         &self.data
     }
-}
\ No newline at end of file
+}
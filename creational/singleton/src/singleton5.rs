@@ -1,35 +1,160 @@
-// 单例模式实现方案5: 使用std::sync::Once (线程安全的延迟初始化)
-use std::sync::Once;
-use std::ptr;
+// 单例模式实现方案5: 读写锁 + 一次性初始化 (线程安全的延迟初始化，读多写少)
+// 早期版本使用std::sync::Once和static mut裸指针，现在是GlobalRwLock的包装，不再需要unsafe
+use crate::global_rwlock::GlobalRwLock;
+use crate::lifecycle::{AccessError, Teardown};
+use crate::notify::{Notifier, Subscription};
+use crate::stats::SingletonStats;
+use crate::try_global::TryInitError;
+use std::fmt::{self, Display};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::IsolationGuard;
+#[cfg(any(test, feature = "testing"))]
+use std::sync::{Mutex, PoisonError};
 
 pub struct Singleton5 {
     data: String,
 }
 
-// 用于确保初始化代码只执行一次
-static ONCE: Once = Once::new();
-// 存储单例实例的原始指针
-static mut INSTANCE5: *mut Singleton5 = ptr::null_mut();
+// 实例的初始数据
+const DEFAULT_DATA5: &str = "Singleton5 instance";
+
+// 首次访问时初始化，记录初始化与访问统计
+// 实例被保存在static中，Drop永远不会执行，因此清理工作登记在生命周期管理器中，
+// 由shutdown()执行，之后的访问返回错误
+static INSTANCE5: GlobalRwLock<Singleton5> =
+    GlobalRwLock::<Singleton5>::instrumented(Singleton5::new, "Singleton5").with_teardown(
+        Teardown {
+            name: "Singleton5",
+            depends_on: &[],
+            hook: |_| println!("Singleton5 is shutting down"),
+        },
+    );
+// 数据变更通知
+static CHANGES5: Notifier<String> = Notifier::new("Singleton5");
+// 测试用的独占锁
+#[cfg(any(test, feature = "testing"))]
+static SERIAL5: Mutex<()> = Mutex::new(());
 
 impl Singleton5 {
-    // 获取单例实例（可变）
-    pub fn get_instance() -> &'static mut Singleton5 {
-        unsafe {
-            ONCE.call_once(|| {
-                // 分配内存并初始化实例
-                INSTANCE5 = Box::into_raw(Box::new(Singleton5 {
-                    data: "Singleton5 instance".to_string(),
-                }));
-            });
-
-            // 将原始指针转换为可变引用
-            &mut *INSTANCE5
+    fn new() -> Singleton5 {
+        Singleton5 {
+            data: DEFAULT_DATA5.to_string(),
+        }
+    }
+
+    // 获取单例实例的访问句柄（内部通过读写锁实现可变性）
+    // 单例已关闭或初始化存在环时panic，需要处理这些场景请使用try_get
+    pub fn get_instance() -> Singleton5Handle {
+        INSTANCE5.get();
+        Singleton5Handle(())
+    }
+
+    // 获取单例实例的访问句柄，shutdown()之后返回AccessError::ShutDown，
+    // 初始化过程中再次访问本单例时返回AccessError::Cycle (而不是死锁)
+    pub fn try_get() -> Result<Singleton5Handle, AccessError> {
+        INSTANCE5.try_get().map(|_| Singleton5Handle(()))
+    }
+
+    // 获取读锁
+    pub fn read() -> RwLockReadGuard<'static, Singleton5> {
+        INSTANCE5.read()
+    }
+
+    // 获取写锁，守卫析构时派发持有写锁期间产生的变更通知
+    pub fn write() -> Singleton5WriteGuard {
+        Singleton5WriteGuard {
+            guard: ManuallyDrop::new(INSTANCE5.write()),
+        }
+    }
+
+    // 订阅数据变更: 每次修改成功后以(旧值, 新值)调用listener，返回的句柄被drop时取消订阅
+    // 回调在释放写锁之后执行，其中可以再次读取或修改单例
+    pub fn subscribe(listener: impl Fn(&str, &str) + Send + Sync + 'static) -> Subscription {
+        CHANGES5.subscribe(move |old: &String, new: &String| listener(old, new))
+    }
+
+    // 以只读方式访问单例实例
+    pub fn with<R>(f: impl FnOnce(&Singleton5) -> R) -> R {
+        f(&Self::read())
+    }
+
+    // 以可变方式访问单例实例
+    pub fn with_mut<R>(f: impl FnOnce(&mut Singleton5) -> R) -> R {
+        f(&mut Self::write())
+    }
+
+    // 原子地读-改-写数据，返回闭包的结果，数据有变化时通知订阅者
+    pub fn update<R>(f: impl FnOnce(&mut String) -> R) -> R {
+        let mut instance = Self::write();
+        if !CHANGES5.has_listeners() {
+            return f(&mut instance.data);
+        }
+        let old = instance.data.clone();
+        let result = f(&mut instance.data);
+        if instance.data != old {
+            CHANGES5.queue(old, instance.data.clone());
         }
+        result
     }
 
-    // 设置数据
+    // 替换数据，返回旧数据
+    pub fn replace(data: &str) -> String {
+        Self::update(|current| std::mem::replace(current, data.to_string()))
+    }
+
+    // 取出数据并留下空字符串
+    pub fn take() -> String {
+        Self::update(std::mem::take)
+    }
+
+    // 获取单例实例 (可失败初始化)
+    // 初始化闭包返回错误时实例保持未初始化，之后的调用可以重试
+    // 初始化闭包中(直接或经由其他单例)再次访问本单例时返回TryInitError::Access(AccessError::Cycle)，
+    // 单例已关闭时返回TryInitError::Access(AccessError::ShutDown)
+    pub fn try_get_instance<F, E>(init: F) -> Result<Singleton5Handle, TryInitError<E>>
+    where
+        F: FnOnce() -> Result<String, E>,
+        E: Display,
+    {
+        INSTANCE5
+            .get_or_try_init(|| init().map(|data| Singleton5 { data }))
+            .map(|_| Singleton5Handle(()))
+    }
+
+    // 初始化与访问统计
+    pub fn stats() -> SingletonStats {
+        INSTANCE5.stats().expect("Singleton5 is instrumented")
+    }
+
+    // 最近一次初始化失败的错误信息
+    pub fn last_error() -> Option<String> {
+        INSTANCE5.last_error()
+    }
+
+    // 恢复为初始数据 (仅用于测试)
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reset() {
+        Self::write().data = DEFAULT_DATA5.to_string();
+    }
+
+    // 独占单例并恢复为初始数据，守卫析构时恢复原数据 (仅用于测试)
+    #[cfg(any(test, feature = "testing"))]
+    pub fn isolate() -> IsolationGuard {
+        let serial = SERIAL5.lock().unwrap_or_else(PoisonError::into_inner);
+        let previous = std::mem::replace(&mut Self::write().data, DEFAULT_DATA5.to_string());
+        IsolationGuard::new(serial, move || Self::write().data = previous)
+    }
+
+    // 设置数据，数据有变化时在释放写锁之后通知订阅者
     pub fn set_data(&mut self, data: &str) {
-        self.data = data.to_string();
+        let old = std::mem::replace(&mut self.data, data.to_string());
+        if CHANGES5.has_listeners() && old != self.data {
+            CHANGES5.queue(old, self.data.clone());
+        }
     }
 
     // 获取数据
@@ -38,9 +163,54 @@ impl Singleton5 {
     }
 }
 
-// 实现Drop trait以释放内存
-impl Drop for Singleton5 {
+// 单例实例的访问句柄，证明实例已经初始化
+// 不暴露内部的RwLock，写锁只能以Singleton5WriteGuard的形式获取，所有修改都会通知订阅者
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Singleton5Handle(());
+
+impl Singleton5Handle {
+    // 获取读锁
+    pub fn read(self) -> RwLockReadGuard<'static, Singleton5> {
+        Singleton5::read()
+    }
+
+    // 获取写锁，守卫析构时派发持有写锁期间产生的变更通知
+    pub fn write(self) -> Singleton5WriteGuard {
+        Singleton5::write()
+    }
+}
+
+// 单例实例的写锁守卫，析构时先释放写锁，再派发持有写锁期间产生的变更通知
+pub struct Singleton5WriteGuard {
+    guard: ManuallyDrop<RwLockWriteGuard<'static, Singleton5>>,
+}
+
+impl Deref for Singleton5WriteGuard {
+    type Target = Singleton5;
+
+    fn deref(&self) -> &Singleton5 {
+        &self.guard
+    }
+}
+
+impl DerefMut for Singleton5WriteGuard {
+    fn deref_mut(&mut self) -> &mut Singleton5 {
+        &mut self.guard
+    }
+}
+
+impl Drop for Singleton5WriteGuard {
     fn drop(&mut self) {
-        println!("Singleton5 is being dropped");
+        // guard只在这里被析构一次
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        CHANGES5.dispatch();
+    }
+}
+
+impl fmt::Debug for Singleton5WriteGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Singleton5WriteGuard")
+            .field(&self.guard.data)
+            .finish()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,72 @@
+// 单例模式实现方案6: 线程级单例 (每个线程一个实例)
+// 特点: 适合线程私有的临时缓冲区、随机数生成器、线程独占连接等场景，访问时无需跨线程加锁
+use crate::per_thread::PerThread;
+use crate::stats::SingletonStats;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+pub struct Singleton6 {
+    data: String,
+    // 当前线程的操作计数 (只有所属线程写入，汇总时由其他线程读取)
+    counter: AtomicU64,
+}
+
+// 每个线程首次访问时创建实例，线程退出时把计数累加到RETIRED6
+static INSTANCE6: PerThread<Singleton6> = PerThread::<Singleton6>::instrumented(
+    || Singleton6 {
+        data: format!(
+            "Singleton6 instance of {}",
+            thread::current().name().unwrap_or("unnamed thread")
+        ),
+        counter: AtomicU64::new(0),
+    },
+    "Singleton6",
+)
+.on_exit(|instance| {
+    RETIRED6.fetch_add(instance.get_count(), Ordering::Relaxed);
+});
+
+// 已退出线程的计数总和
+static RETIRED6: AtomicU64 = AtomicU64::new(0);
+
+impl Singleton6 {
+    // 访问当前线程的单例实例
+    pub fn with<R>(f: impl FnOnce(&Singleton6) -> R) -> R {
+        INSTANCE6.with(f)
+    }
+
+    // 所有存活线程的计数总和
+    pub fn live_count_sum() -> u64 {
+        INSTANCE6.fold(0, |sum, instance| sum + instance.get_count())
+    }
+
+    // 所有线程(包括已退出线程)的计数总和
+    pub fn total_count() -> u64 {
+        RETIRED6.load(Ordering::Relaxed) + Self::live_count_sum()
+    }
+
+    // 存活的线程实例数量
+    pub fn live_instances() -> usize {
+        INSTANCE6.live_count()
+    }
+
+    // 所有线程的访问统计，初始化信息为第一个线程实例的创建
+    pub fn stats() -> SingletonStats {
+        INSTANCE6.stats().expect("Singleton6 is instrumented")
+    }
+
+    // 计数加一，返回新的计数
+    pub fn increment(&self) -> u64 {
+        self.counter.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    // 获取计数
+    pub fn get_count(&self) -> u64 {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    // 获取数据
+    pub fn get_data(&self) -> &str {
+        &self.data
+    }
+}
@@ -0,0 +1,408 @@
+// 可持久化的单例: Persistent<T>
+// 计数器、缓存等全局状态需要在重启后保留。Persistent在GlobalMutex的基础上:
+// 1. 首次访问时从快照文件恢复，文件不存在时调用初始化闭包
+// 2. checkpoint把当前状态原子地写入快照文件 (先写临时文件再重命名，崩溃时不会留下半个文件)
+// 3. auto_checkpoint在后台线程中定期保存有修改的状态，checkpoint_on_shutdown在关闭时保存
+// 快照损坏或版本不匹配时不会panic: 坏文件被改名保留，状态回退到初始化闭包的结果
+use crate::lifecycle::{AccessError, Lifecycle};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// 可以保存为快照的类型
+pub trait Snapshot: Sized {
+    // 快照格式的版本号，数据结构不兼容地变化时递增
+    const VERSION: u32;
+
+    // 序列化为字节
+    fn encode(&self) -> Vec<u8>;
+
+    // 从字节反序列化，数据无效时返回错误信息
+    fn decode(data: &[u8]) -> Result<Self, String>;
+
+    // 从旧版本的快照迁移，默认不支持迁移
+    fn migrate(version: u32, data: &[u8]) -> Result<Self, String> {
+        let _ = data;
+        Err(format!("cannot migrate from version {}", version))
+    }
+}
+
+// 数值、布尔值和字符串以文本形式保存
+macro_rules! impl_snapshot_via_str {
+    ($($ty:ty),*) => {
+        $(
+            impl Snapshot for $ty {
+                const VERSION: u32 = 1;
+
+                fn encode(&self) -> Vec<u8> {
+                    self.to_string().into_bytes()
+                }
+
+                fn decode(data: &[u8]) -> Result<Self, String> {
+                    let text = std::str::from_utf8(data).map_err(|err| err.to_string())?;
+                    text.parse().map_err(|err: <$ty as std::str::FromStr>::Err| err.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_snapshot_via_str!(
+    u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, bool, String
+);
+
+// 快照相关的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    // 读写快照文件失败
+    Io {
+        path: String,
+        message: String,
+    },
+    // 快照文件损坏: 格式错误、长度或校验和不匹配、无法反序列化
+    Corrupt {
+        path: String,
+        reason: String,
+    },
+    // 快照的版本与类型的VERSION不一致且无法迁移
+    VersionMismatch {
+        path: String,
+        found: u32,
+        expected: u32,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io { path, message } => {
+                write!(f, "cannot access snapshot `{}`: {}", path, message)
+            }
+            SnapshotError::Corrupt { path, reason } => {
+                write!(f, "snapshot `{}` is corrupt: {}", path, reason)
+            }
+            SnapshotError::VersionMismatch {
+                path,
+                found,
+                expected,
+            } => write!(
+                f,
+                "snapshot `{}` has version {}, expected {}",
+                path, found, expected
+            ),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+// 快照文件格式: 头部一行 + 数据
+// SNAPSHOT <版本号> <数据长度> <FNV-1a校验和>\n<数据>
+const MAGIC: &str = "SNAPSHOT";
+
+// FNV-1a 64位散列，用于发现截断或被改写的快照
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn encode_file<T: Snapshot>(value: &T) -> Vec<u8> {
+    let data = value.encode();
+    let mut file = format!(
+        "{} {} {} {:016x}\n",
+        MAGIC,
+        T::VERSION,
+        data.len(),
+        checksum(&data)
+    )
+    .into_bytes();
+    file.extend_from_slice(&data);
+    file
+}
+
+fn decode_file<T: Snapshot>(path: &Path, file: &[u8]) -> Result<T, SnapshotError> {
+    let corrupt = |reason: &str| SnapshotError::Corrupt {
+        path: path.display().to_string(),
+        reason: reason.to_string(),
+    };
+    let newline = file
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .ok_or_else(|| corrupt("missing header"))?;
+    let header = std::str::from_utf8(&file[..newline]).map_err(|_| corrupt("invalid header"))?;
+    let data = &file[newline + 1..];
+    let fields: Vec<&str> = header.split(' ').collect();
+    let [MAGIC, version, len, sum] = fields[..] else {
+        return Err(corrupt("invalid header"));
+    };
+    let version: u32 = version.parse().map_err(|_| corrupt("invalid version"))?;
+    let len: usize = len.parse().map_err(|_| corrupt("invalid length"))?;
+    let sum = u64::from_str_radix(sum, 16).map_err(|_| corrupt("invalid checksum"))?;
+    if data.len() != len {
+        return Err(corrupt(&format!(
+            "expected {} bytes of data, found {}",
+            len,
+            data.len()
+        )));
+    }
+    if checksum(data) != sum {
+        return Err(corrupt("checksum mismatch"));
+    }
+    if version == T::VERSION {
+        T::decode(data).map_err(|reason| corrupt(&reason))
+    } else {
+        T::migrate(version, data).map_err(|_| SnapshotError::VersionMismatch {
+            path: path.display().to_string(),
+            found: version,
+            expected: T::VERSION,
+        })
+    }
+}
+
+// 原子地写入文件: 先写同目录下的临时文件并落盘，再重命名覆盖目标文件
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+pub struct Persistent<T, F = fn() -> T> {
+    path: &'static str,
+    cell: OnceLock<Mutex<T>>,
+    init: F,
+    // 上次保存之后是否可能有修改
+    dirty: AtomicBool,
+    // 保证同一时间只有一个checkpoint在写文件，后保存的状态不会被先保存的覆盖
+    writing: Mutex<()>,
+    // 最近一次恢复或保存失败的错误
+    last_error: Mutex<Option<SnapshotError>>,
+}
+
+impl<T, F> Persistent<T, F> {
+    // 创建可持久化的单例，可用于static声明
+    // 首次访问时从path恢复，快照不存在或无法使用时调用init
+    pub const fn new(path: &'static str, init: F) -> Self {
+        Persistent {
+            path,
+            cell: OnceLock::new(),
+            init,
+            dirty: AtomicBool::new(false),
+            writing: Mutex::new(()),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    // 快照文件路径
+    pub fn path(&self) -> &Path {
+        Path::new(self.path)
+    }
+
+    // 是否已经初始化 (恢复或调用init)
+    pub fn is_initialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+
+    // 最近一次恢复或保存失败的错误
+    pub fn last_error(&self) -> Option<SnapshotError> {
+        self.lock_last_error().clone()
+    }
+
+    fn lock_last_error(&self) -> MutexGuard<'_, Option<SnapshotError>> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Snapshot, F: Fn() -> T> Persistent<T, F> {
+    // 加锁访问实例，首次访问时恢复快照
+    // 返回可写的守卫，因此每次加锁都视为有修改，下次checkpoint会保存
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        let guard = self.lock_value();
+        self.dirty.store(true, Ordering::Release);
+        guard
+    }
+
+    // 原子地读-改-写，返回闭包的结果
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    // 当前值的副本
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock_value().clone()
+    }
+
+    // 把当前状态保存到快照文件
+    pub fn checkpoint(&self) -> Result<(), SnapshotError> {
+        let _writing = self.writing.lock().unwrap_or_else(PoisonError::into_inner);
+        let contents = {
+            let value = self.lock_value();
+            self.dirty.store(false, Ordering::Release);
+            encode_file(&*value)
+        };
+        write_atomically(self.path(), &contents).map_err(|err| {
+            // 保存失败时保留修改标记，下次自动保存会重试
+            self.dirty.store(true, Ordering::Release);
+            self.record_error(SnapshotError::Io {
+                path: self.path.to_string(),
+                message: err.to_string(),
+            })
+        })
+    }
+
+    // 有修改时才保存，返回是否写入了文件
+    pub fn checkpoint_if_dirty(&self) -> Result<bool, SnapshotError> {
+        if self.dirty.load(Ordering::Acquire) {
+            self.checkpoint().map(|()| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // 首次访问时的恢复: 快照不存在时调用init；快照无法使用时记录错误，
+    // 把坏文件改名为<path>.bad保留现场，然后调用init
+    fn restore(&self) -> T {
+        let path = self.path();
+        let file = match fs::read(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return (self.init)(),
+            Err(err) => {
+                self.record_error(SnapshotError::Io {
+                    path: self.path.to_string(),
+                    message: err.to_string(),
+                });
+                return (self.init)();
+            }
+        };
+        match decode_file(path, &file) {
+            Ok(value) => value,
+            Err(err) => {
+                self.record_error(err);
+                let mut bad = path.as_os_str().to_owned();
+                bad.push(".bad");
+                let _ = fs::rename(path, bad);
+                (self.init)()
+            }
+        }
+    }
+
+    fn lock_value(&self) -> MutexGuard<'_, T> {
+        self.cell
+            .get_or_init(|| Mutex::new(self.restore()))
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn record_error(&self, err: SnapshotError) -> SnapshotError {
+        *self.lock_last_error() = Some(err.clone());
+        err
+    }
+}
+
+impl<T, F> Persistent<T, F>
+where
+    T: Snapshot + Send + 'static,
+    F: Fn() -> T + Sync + 'static,
+{
+    // 启动后台线程，每隔interval保存一次有修改的状态
+    // 返回的句柄被drop或调用stop时停止线程，并在停止前最后保存一次
+    pub fn auto_checkpoint(&'static self, interval: Duration) -> Checkpointer {
+        let (stop, stopped) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            loop {
+                let result = stopped.recv_timeout(interval);
+                // 失败已记录在last_error中，下一轮会重试
+                let _ = self.checkpoint_if_dirty();
+                if result != Err(RecvTimeoutError::Timeout) {
+                    break;
+                }
+            }
+        });
+        Checkpointer {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    // 在生命周期管理器中登记保存回调，shutdown()时保存有修改的状态
+    pub fn checkpoint_on_shutdown(
+        &'static self,
+        name: &'static str,
+        lifecycle: &Lifecycle,
+    ) -> Result<(), AccessError> {
+        lifecycle.register(name, &[], move || {
+            let _ = self.checkpoint_if_dirty();
+        })
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Persistent<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Persistent");
+        debug.field("path", &self.path);
+        match self.cell.get().map(Mutex::try_lock) {
+            Some(Ok(value)) => debug.field("value", &*value),
+            Some(Err(_)) => debug.field("value", &format_args!("<locked>")),
+            None => debug.field("value", &format_args!("<uninit>")),
+        };
+        debug.finish()
+    }
+}
+
+// 自动保存线程的句柄
+pub struct Checkpointer {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Checkpointer {
+    // 停止自动保存，等待最后一次保存完成
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Checkpointer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl fmt::Debug for Checkpointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Checkpointer")
+            .field("running", &self.handle.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,237 @@
+// 自旋锁实现的同步原语: SpinOnce和SpinMutex
+// 只依赖core，在no_std环境(固件、内核)中代替std::sync::OnceLock和std::sync::Mutex。
+// 等待时忙等(spin_loop)而不是让出线程，适合临界区很短、没有操作系统调度的场景
+use core::cell::UnsafeCell;
+use core::convert::Infallible;
+use core::fmt;
+use core::hint;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+const INCOMPLETE: u8 = 0;
+const RUNNING: u8 = 1;
+const COMPLETE: u8 = 2;
+
+// 只能写入一次的单元，对应std::sync::OnceLock
+// 注意: 没有线程本地存储可用，无法检测初始化环，初始化闭包中再次访问同一个单元会永远自旋
+pub struct SpinOnce<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// 实例会在线程间共享(Sync)并可能在其他线程被释放(Send)
+unsafe impl<T: Send + Sync> Sync for SpinOnce<T> {}
+unsafe impl<T: Send> Send for SpinOnce<T> {}
+
+// 初始化闭包panic或返回错误时把状态恢复为未初始化，其他线程可以重试
+struct ResetOnDrop<'a>(&'a AtomicU8);
+
+impl Drop for ResetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(INCOMPLETE, Ordering::Release);
+    }
+}
+
+impl<T> SpinOnce<T> {
+    // 创建空单元，可用于static声明
+    pub const fn new() -> Self {
+        SpinOnce {
+            state: AtomicU8::new(INCOMPLETE),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    // 是否已经写入
+    pub fn is_initialized(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+
+    // 已写入的值，尚未写入时返回None
+    pub fn get(&self) -> Option<&T> {
+        if self.is_initialized() {
+            // COMPLETE状态下值已写入且不会再被修改
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    // 获取值，尚未写入时调用f，多个线程并发调用时f只执行一次
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        match self.get_or_try_init(|| Ok::<T, Infallible>(f())) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    // 获取值，尚未写入时调用f；f返回错误时保持未初始化，之后的调用可以重试
+    pub fn get_or_try_init<E>(&self, f: impl FnOnce() -> Result<T, E>) -> Result<&T, E> {
+        loop {
+            match self.state.compare_exchange(
+                INCOMPLETE,
+                RUNNING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    let reset = ResetOnDrop(&self.state);
+                    let value = f()?;
+                    // 只有把状态从INCOMPLETE改为RUNNING的线程会写入
+                    unsafe { (*self.value.get()).write(value) };
+                    mem::forget(reset);
+                    self.state.store(COMPLETE, Ordering::Release);
+                    return Ok(unsafe { (*self.value.get()).assume_init_ref() });
+                }
+                Err(COMPLETE) => return Ok(unsafe { (*self.value.get()).assume_init_ref() }),
+                Err(_) => {
+                    // 其他线程正在初始化
+                    while self.state.load(Ordering::Acquire) == RUNNING {
+                        hint::spin_loop();
+                    }
+                }
+            }
+        }
+    }
+
+    // 写入值，已经写入过时交还value
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(()),
+            Some(rejected) => Err(rejected),
+        }
+    }
+}
+
+impl<T> Default for SpinOnce<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SpinOnce<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => f.debug_tuple("SpinOnce").field(value).finish(),
+            None => f.write_str("SpinOnce(<uninit>)"),
+        }
+    }
+}
+
+impl<T> Drop for SpinOnce<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == COMPLETE {
+            unsafe { self.value.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+// 自旋互斥锁，对应std::sync::Mutex
+// 没有中毒状态: 持有锁的线程panic时守卫照常释放锁，数据保持panic时的样子
+pub struct SpinMutex<T: ?Sized> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Sync for SpinMutex<T> {}
+unsafe impl<T: ?Sized + Send> Send for SpinMutex<T> {}
+
+impl<T> SpinMutex<T> {
+    // 创建互斥锁，可用于static声明
+    pub const fn new(value: T) -> Self {
+        SpinMutex {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    // 取出数据
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> SpinMutex<T> {
+    // 加锁，锁被占用时自旋等待
+    pub fn lock(&self) -> SpinMutexGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+            // 只读地等待，避免在锁被占用期间反复写缓存行
+            while self.locked.load(Ordering::Relaxed) {
+                hint::spin_loop();
+            }
+        }
+    }
+
+    // 尝试加锁，锁被占用时返回None
+    pub fn try_lock(&self) -> Option<SpinMutexGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| SpinMutexGuard { mutex: self })
+    }
+
+    // 是否被占用
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    // 独占访问数据，不需要加锁
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for SpinMutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SpinMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_lock() {
+            Some(value) => f.debug_tuple("SpinMutex").field(&&*value).finish(),
+            None => f.write_str("SpinMutex(<locked>)"),
+        }
+    }
+}
+
+// 自旋锁守卫，析构时释放锁
+pub struct SpinMutexGuard<'a, T: ?Sized> {
+    mutex: &'a SpinMutex<T>,
+}
+
+// 守卫只提供&T，可以在线程间共享的条件与&T相同
+unsafe impl<T: ?Sized + Sync> Sync for SpinMutexGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for SpinMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // 持有守卫期间独占数据
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for SpinMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for SpinMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.locked.store(false, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SpinMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
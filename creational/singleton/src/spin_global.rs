@@ -0,0 +1,197 @@
+// no_std环境下的通用单例容器: SpinGlobal、SpinGlobalMutex、SpinHotSwap(需要alloc)
+// 与Global、GlobalMutex、HotSwap的用法相同，基于自旋锁实现，只依赖core(和alloc)。
+// 关闭std特性时以Global/GlobalMutex/HotSwap的名称导出，同一份代码可以在两种环境下编译；
+// 没有生命周期管理、统计和初始化环检测，这些功能需要std
+use crate::spin::{SpinMutex, SpinMutexGuard, SpinOnce};
+use core::fmt;
+use core::mem;
+use core::ops::Deref;
+
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+
+pub struct SpinGlobal<T, F = fn() -> T> {
+    cell: SpinOnce<T>,
+    init: F,
+}
+
+impl<T, F> SpinGlobal<T, F> {
+    // 创建单例容器，可用于static声明，首次访问时调用init
+    pub const fn new(init: F) -> Self {
+        SpinGlobal {
+            cell: SpinOnce::new(),
+            init,
+        }
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.cell.is_initialized()
+    }
+
+    // 已初始化的实例，尚未初始化时返回None而不触发初始化
+    pub fn get_if_initialized(&self) -> Option<&T> {
+        self.cell.get()
+    }
+}
+
+impl<T, F: Fn() -> T> SpinGlobal<T, F> {
+    // 获取单例实例 (懒汉式: 首次访问时初始化)
+    pub fn get(&self) -> &T {
+        self.cell.get_or_init(&self.init)
+    }
+
+    // 立即初始化 (饿汉式: 在启动代码中调用，之后的访问不再有初始化开销)
+    pub fn force(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T, F: Fn() -> T> Deref for SpinGlobal<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for SpinGlobal<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_if_initialized() {
+            Some(value) => f.debug_tuple("SpinGlobal").field(value).finish(),
+            None => f.write_str("SpinGlobal(<uninit>)"),
+        }
+    }
+}
+
+pub struct SpinGlobalMutex<T, F = fn() -> T> {
+    cell: SpinOnce<SpinMutex<T>>,
+    init: F,
+}
+
+impl<T, F> SpinGlobalMutex<T, F> {
+    // 创建单例容器，可用于static声明，首次访问时调用init
+    pub const fn new(init: F) -> Self {
+        SpinGlobalMutex {
+            cell: SpinOnce::new(),
+            init,
+        }
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.cell.is_initialized()
+    }
+}
+
+impl<T, F: Fn() -> T> SpinGlobalMutex<T, F> {
+    // 加锁访问实例，首次访问时初始化
+    pub fn lock(&self) -> SpinMutexGuard<'_, T> {
+        self.cell
+            .get_or_init(|| SpinMutex::new((self.init)()))
+            .lock()
+    }
+
+    // 原子地读-改-写，返回闭包的结果
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    // 替换为新值，返回旧值
+    pub fn replace(&self, value: T) -> T {
+        mem::replace(&mut *self.lock(), value)
+    }
+
+    // 取出当前值并留下默认值
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        mem::take(&mut *self.lock())
+    }
+
+    // 当前值的副本
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock().clone()
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for SpinGlobalMutex<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell.get().map(SpinMutex::try_lock) {
+            Some(Some(value)) => f.debug_tuple("SpinGlobalMutex").field(&*value).finish(),
+            Some(None) => f.write_str("SpinGlobalMutex(<locked>)"),
+            None => f.write_str("SpinGlobalMutex(<uninit>)"),
+        }
+    }
+}
+
+// 可热替换的单例，读者获取Arc快照，写者发布新值 (需要alloc特性)
+// 与HotSwap不同，读取也需要短暂地加自旋锁
+#[cfg(feature = "alloc")]
+pub struct SpinHotSwap<T, F = fn() -> T> {
+    current: SpinMutex<Option<Arc<T>>>,
+    init: F,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F> SpinHotSwap<T, F> {
+    // 创建单例容器，可用于static声明，首次读取时调用init创建初始值
+    pub const fn new(init: F) -> Self {
+        SpinHotSwap {
+            current: SpinMutex::new(None),
+            init,
+        }
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.current.lock().is_some()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F: Fn() -> T> SpinHotSwap<T, F> {
+    // 当前值的快照，首次读取时初始化
+    // init在锁内执行，其中不能访问本单例
+    pub fn load(&self) -> Arc<T> {
+        let mut current = self.current.lock();
+        Arc::clone(current.get_or_insert_with(|| Arc::new((self.init)())))
+    }
+
+    // 发布新值，已发出的旧快照不受影响
+    pub fn store(&self, value: T) {
+        self.swap(value);
+    }
+
+    // 发布新值并返回旧值的快照，尚未初始化时返回None (不会为此调用init)
+    pub fn swap(&self, value: T) -> Option<Arc<T>> {
+        self.current.lock().replace(Arc::new(value))
+    }
+
+    // 基于当前值计算新值并发布，返回新值的快照
+    // 整个过程持有锁，并发的update不会丢失更新
+    pub fn update(&self, f: impl FnOnce(&T) -> T) -> Arc<T> {
+        let mut current = self.current.lock();
+        let old = current.get_or_insert_with(|| Arc::new((self.init)()));
+        let new = Arc::new(f(old));
+        *current = Some(Arc::clone(&new));
+        new
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: fmt::Debug, F> fmt::Debug for SpinHotSwap<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.current.try_lock() {
+            Some(current) => match &*current {
+                Some(value) => f.debug_tuple("SpinHotSwap").field(value).finish(),
+                None => f.write_str("SpinHotSwap(<uninit>)"),
+            },
+            None => f.write_str("SpinHotSwap(<locked>)"),
+        }
+    }
+}
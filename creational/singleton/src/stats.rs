@@ -0,0 +1,206 @@
+// 单例的初始化诊断与访问统计: Instrument
+// 排查启动问题时需要知道单例何时、由哪个线程初始化，初始化花了多久，
+// 以及运行期间被访问了多少次、等待锁花了多少时间。
+// 容器通过Instrument记录这些信息 (可选，未开启时没有任何开销)，
+// 所有开启了统计的单例在首次记录时登记到进程级列表，dump_stats()可以一次列出
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// 某个单例的统计快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SingletonStats {
+    pub name: &'static str,
+    // 开始初始化的时间，None表示尚未初始化
+    pub initialized_at: Option<SystemTime>,
+    // 初始化闭包的执行时间
+    pub init_duration: Option<Duration>,
+    // 执行初始化的线程名称，未命名的线程为None
+    pub init_thread: Option<String>,
+    // 访问次数 (包括触发初始化的那一次)
+    pub accesses: u64,
+    // 累计等待锁的时间
+    pub lock_wait: Duration,
+}
+
+impl fmt::Display for SingletonStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.name)?;
+        match (self.initialized_at, self.init_duration) {
+            (Some(at), Some(duration)) => {
+                let since_epoch = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+                write!(
+                    f,
+                    "initialized at {}.{:03}s by thread `{}` in {:?}",
+                    since_epoch.as_secs(),
+                    since_epoch.subsec_millis(),
+                    self.init_thread.as_deref().unwrap_or("<unnamed>"),
+                    duration
+                )?;
+            }
+            _ => f.write_str("not initialized")?,
+        }
+        write!(
+            f,
+            ", {} accesses, {:?} lock wait",
+            self.accesses, self.lock_wait
+        )
+    }
+}
+
+// 初始化信息，初始化完成时写入一次
+struct InitInfo {
+    at: SystemTime,
+    duration: Duration,
+    thread: Option<String>,
+}
+
+struct Recorder {
+    name: &'static str,
+    init: OnceLock<InitInfo>,
+    accesses: AtomicU64,
+    lock_wait_nanos: AtomicU64,
+}
+
+impl Recorder {
+    fn snapshot(&self) -> SingletonStats {
+        let init = self.init.get();
+        SingletonStats {
+            name: self.name,
+            initialized_at: init.map(|init| init.at),
+            init_duration: init.map(|init| init.duration),
+            init_thread: init.and_then(|init| init.thread.clone()),
+            accesses: self.accesses.load(Ordering::Relaxed),
+            lock_wait: Duration::from_nanos(self.lock_wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+// 所有开启了统计的单例 (按首次记录的顺序)
+static INSTRUMENTED: Mutex<Vec<Arc<Recorder>>> = Mutex::new(Vec::new());
+
+// 单例的统计记录器，嵌入在单例容器中
+pub struct Instrument {
+    name: &'static str,
+    recorder: OnceLock<Arc<Recorder>>,
+}
+
+impl Instrument {
+    // 创建记录器，可用于static声明
+    pub const fn new(name: &'static str) -> Self {
+        Instrument {
+            name,
+            recorder: OnceLock::new(),
+        }
+    }
+
+    // 单例名称
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    // 当前的统计快照
+    pub fn stats(&self) -> SingletonStats {
+        self.recorder().snapshot()
+    }
+
+    fn recorder(&self) -> &Recorder {
+        self.recorder.get_or_init(|| {
+            let recorder = Arc::new(Recorder {
+                name: self.name,
+                init: OnceLock::new(),
+                accesses: AtomicU64::new(0),
+                lock_wait_nanos: AtomicU64::new(0),
+            });
+            INSTRUMENTED
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(Arc::clone(&recorder));
+            recorder
+        })
+    }
+
+    // 开始计时初始化，调用InitTimer::finish表示初始化成功
+    // 初始化失败或panic时丢弃计时器即可，不会留下记录
+    pub(crate) fn start_init(&self) -> InitTimer<'_> {
+        InitTimer {
+            instrument: self,
+            at: SystemTime::now(),
+            started: Instant::now(),
+        }
+    }
+
+    // 执行并记录不会失败的初始化
+    pub(crate) fn record_init<T>(&self, init: impl FnOnce() -> T) -> T {
+        let timer = self.start_init();
+        let value = init();
+        timer.finish();
+        value
+    }
+
+    // 记录一次访问
+    pub(crate) fn record_access(&self) {
+        self.recorder().accesses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // 记录一次加锁访问及等待锁的时间
+    pub(crate) fn acquire<G>(&self, lock: impl FnOnce() -> G) -> G {
+        let started = Instant::now();
+        let guard = lock();
+        self.record_lock_wait(started.elapsed());
+        self.record_access();
+        guard
+    }
+
+    // 记录等待锁的时间
+    pub(crate) fn record_lock_wait(&self, waited: Duration) {
+        let nanos = u64::try_from(waited.as_nanos()).unwrap_or(u64::MAX);
+        self.recorder()
+            .lock_wait_nanos
+            .fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Debug for Instrument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.stats(), f)
+    }
+}
+
+// 初始化计时器
+pub(crate) struct InitTimer<'a> {
+    instrument: &'a Instrument,
+    at: SystemTime,
+    started: Instant,
+}
+
+impl InitTimer<'_> {
+    // 初始化成功，记录初始化信息 (重复初始化时只保留第一次)
+    pub(crate) fn finish(self) {
+        let _ = self.instrument.recorder().init.set(InitInfo {
+            at: self.at,
+            duration: self.started.elapsed(),
+            thread: thread::current().name().map(str::to_owned),
+        });
+    }
+}
+
+// 所有开启了统计的单例的统计快照
+pub fn all_stats() -> Vec<SingletonStats> {
+    INSTRUMENTED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|recorder| recorder.snapshot())
+        .collect()
+}
+
+// 所有开启了统计的单例的统计信息，每行一个单例
+pub fn dump_stats() -> String {
+    all_stats()
+        .iter()
+        .map(|stats| format!("{}\n", stats))
+        .collect()
+}
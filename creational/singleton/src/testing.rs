@@ -0,0 +1,62 @@
+// 测试辅助API (需要开启testing特性，本crate的单元测试自动启用)
+// 单例是进程级的全局状态，测试并行运行时会互相干扰，这里提供:
+// 1. OverrideGuard: 临时替换Global/TryGlobal的实例，离开作用域时恢复原实例
+// 2. IsolationGuard: 独占方案2/方案5的单例并恢复为初始数据，离开作用域时恢复原数据
+// 同一个单例的多个守卫会互相等待，因此并行的测试各自看到自己的实例
+use std::sync::MutexGuard;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// 单例实例替换守卫
+// 注意: 被替换下来的实例和替换用的实例都会被泄漏而不是释放，
+// 因为测试代码中可能仍持有它们的引用；同一个单例上不要嵌套替换，否则会死锁
+// 替换用的实例由容器按自己的方式分配(Global用Arc，TryGlobal用Box)，
+// 守卫被mem::forget时替换用的实例留在容器中，由容器析构时按同样的方式释放
+pub struct OverrideGuard<'a, T> {
+    slot: &'a AtomicPtr<T>,
+    previous: *mut T,
+    _serial: MutexGuard<'a, ()>,
+}
+
+impl<'a, T> OverrideGuard<'a, T> {
+    pub(crate) fn new(
+        slot: &'a AtomicPtr<T>,
+        replacement: *mut T,
+        serial: MutexGuard<'a, ()>,
+    ) -> Self {
+        let previous = slot.swap(replacement, Ordering::AcqRel);
+        OverrideGuard {
+            slot,
+            previous,
+            _serial: serial,
+        }
+    }
+}
+
+impl<T> Drop for OverrideGuard<'_, T> {
+    fn drop(&mut self) {
+        self.slot.store(self.previous, Ordering::Release);
+    }
+}
+
+// 单例独占守卫，析构时先执行恢复操作，再释放独占锁
+pub struct IsolationGuard {
+    restore: Option<Box<dyn FnOnce()>>,
+    _serial: MutexGuard<'static, ()>,
+}
+
+impl IsolationGuard {
+    pub(crate) fn new(serial: MutexGuard<'static, ()>, restore: impl FnOnce() + 'static) -> Self {
+        IsolationGuard {
+            restore: Some(Box::new(restore)),
+            _serial: serial,
+        }
+    }
+}
+
+impl Drop for IsolationGuard {
+    fn drop(&mut self) {
+        if let Some(restore) = self.restore.take() {
+            restore();
+        }
+    }
+}
@@ -0,0 +1,260 @@
+// 可失败初始化的通用单例容器: TryGlobal<T, E>
+// 特点: 初始化闭包返回Result，失败时单例保持未初始化状态，下次访问会重新尝试，
+// 并记录最近一次的错误以便诊断 (对比Once: 初始化闭包panic后Once会永久中毒)
+use crate::cycle::{self, InitGuard};
+use crate::lifecycle::AccessError;
+use crate::stats::{Instrument, SingletonStats};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::OverrideGuard;
+
+pub struct TryGlobal<T, E, F = fn() -> Result<T, E>> {
+    // 已初始化实例的指针，空指针表示尚未初始化
+    ptr: AtomicPtr<T>,
+    // 初始化锁，同时保存最近一次初始化失败的错误
+    last_error: Mutex<Option<E>>,
+    init: F,
+    // 开启统计时的记录器
+    instrument: Option<Instrument>,
+    // 测试用的独占锁，保证同一时间只有一个替换守卫
+    #[cfg(any(test, feature = "testing"))]
+    serial: Mutex<()>,
+    _marker: PhantomData<T>,
+}
+
+// 可失败初始化的错误: 初始化闭包返回的错误，或者无法访问单例
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryInitError<E> {
+    // 初始化闭包返回的错误
+    Init(E),
+    // 初始化过程中(直接或经由其他单例)再次访问了本单例，或者单例已经关闭
+    Access(AccessError),
+}
+
+impl<E> From<AccessError> for TryInitError<E> {
+    fn from(err: AccessError) -> Self {
+        TryInitError::Access(err)
+    }
+}
+
+impl<E: Display> fmt::Display for TryInitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryInitError::Init(err) => write!(f, "initialization failed: {}", err),
+            TryInitError::Access(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + Display> Error for TryInitError<E> {}
+
+unsafe impl<T: Send + Sync, E: Send, F: Sync> Sync for TryGlobal<T, E, F> {}
+unsafe impl<T: Send, E: Send, F: Send> Send for TryGlobal<T, E, F> {}
+
+impl<T, E, F> TryGlobal<T, E, F> {
+    // 创建单例容器，可用于static声明
+    pub const fn new(init: F) -> Self {
+        Self::build(init, None)
+    }
+
+    // 创建开启统计的单例容器，只记录成功的初始化
+    pub const fn instrumented(init: F, name: &'static str) -> Self {
+        Self::build(init, Some(Instrument::new(name)))
+    }
+
+    const fn build(init: F, instrument: Option<Instrument>) -> Self {
+        TryGlobal {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            last_error: Mutex::new(None),
+            init,
+            instrument,
+            #[cfg(any(test, feature = "testing"))]
+            serial: Mutex::new(()),
+            _marker: PhantomData,
+        }
+    }
+
+    // 统计信息，未开启统计时返回None
+    pub fn stats(&self) -> Option<SingletonStats> {
+        self.instrument.as_ref().map(Instrument::stats)
+    }
+
+    // 是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        !self.ptr.load(Ordering::Acquire).is_null()
+    }
+
+    // 获取已初始化的实例，未初始化时返回None且不会触发初始化
+    pub fn get_if_initialized(&self) -> Option<&T> {
+        let p = self.ptr.load(Ordering::Acquire);
+        unsafe { p.as_ref() }
+    }
+
+    // 重置为未初始化状态，下次访问会重新执行初始化闭包 (仅用于测试)
+    // 旧实例会被泄漏而不是释放，之前获取的引用仍然有效
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reset(&self) {
+        let _guard = self
+            .last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.ptr.store(ptr::null_mut(), Ordering::Release);
+    }
+
+    // 临时替换单例实例，守卫析构时恢复原实例 (仅用于测试)
+    // 同一单例的多个替换守卫会依次等待，并行的测试各自看到自己的实例
+    #[cfg(any(test, feature = "testing"))]
+    pub fn override_with(&self, value: T) -> OverrideGuard<'_, T> {
+        let serial = self.serial.lock().unwrap_or_else(PoisonError::into_inner);
+        let _guard = self
+            .last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // 与初始化路径一样由Box分配
+        OverrideGuard::new(&self.ptr, Box::into_raw(Box::new(value)), serial)
+    }
+}
+
+impl<T, E: Clone, F> TryGlobal<T, E, F> {
+    // 最近一次初始化失败的错误 (初始化成功后仍保留，便于排查启动问题)
+    pub fn last_error(&self) -> Option<E> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl<T, E: Clone, F: Fn() -> Result<T, E>> TryGlobal<T, E, F> {
+    // 获取单例实例，首次访问时执行初始化，失败时返回TryInitError::Init(初始化闭包的错误)，
+    // 初始化闭包再次访问本单例时返回TryInitError::Access(AccessError::Cycle)
+    pub fn get(&self) -> Result<&T, TryInitError<E>> {
+        if let Some(instrument) = &self.instrument {
+            instrument.record_access();
+        }
+        if let Some(value) = self.get_if_initialized() {
+            return Ok(value);
+        }
+        self.init_slow()
+    }
+
+    #[cold]
+    fn init_slow(&self) -> Result<&T, TryInitError<E>> {
+        // 初始化闭包再次访问本单例时返回错误，而不是在初始化锁上死锁
+        let name = match &self.instrument {
+            Some(instrument) => instrument.name(),
+            None => std::any::type_name::<T>(),
+        };
+        let _init = cycle::enter(self, name)?;
+        let started = Instant::now();
+        let mut last_error = self
+            .last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(instrument) = &self.instrument {
+            instrument.record_lock_wait(started.elapsed());
+        }
+        if let Some(value) = self.get_if_initialized() {
+            return Ok(value);
+        }
+        let timer = self.instrument.as_ref().map(Instrument::start_init);
+        match (self.init)() {
+            Ok(value) => {
+                if let Some(timer) = timer {
+                    timer.finish();
+                }
+                let p = Box::into_raw(Box::new(value));
+                self.ptr.store(p, Ordering::Release);
+                Ok(unsafe { &*p })
+            }
+            Err(err) => {
+                *last_error = Some(err.clone());
+                Err(TryInitError::Init(err))
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, E, F> fmt::Debug for TryGlobal<T, E, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_if_initialized() {
+            Some(value) => f.debug_tuple("TryGlobal").field(value).finish(),
+            None => f.write_str("TryGlobal(<uninit>)"),
+        }
+    }
+}
+
+impl<T, E, F> Drop for TryGlobal<T, E, F> {
+    fn drop(&mut self) {
+        let p = *self.ptr.get_mut();
+        if !p.is_null() {
+            drop(unsafe { Box::from_raw(p) });
+        }
+    }
+}
+
+// 按调用传入初始化闭包的可失败初始化，方案3(OnceLock)和GlobalRwLock(方案5)共用
+// 实例保存在哪里由调用方决定: get检查是否已初始化，publish保存初始化结果
+pub(crate) struct TryInit {
+    // 初始化锁，同时记录最近一次初始化失败的错误信息
+    last_error: Mutex<Option<String>>,
+}
+
+impl TryInit {
+    pub(crate) const fn new() -> Self {
+        TryInit {
+            last_error: Mutex::new(None),
+        }
+    }
+
+    // 进入初始化，初始化过程中再次进入时返回AccessError::Cycle
+    // 同一个单例的所有初始化路径都应以此为准，否则无法发现跨路径的环
+    pub(crate) fn enter(&self, name: &'static str) -> Result<InitGuard, AccessError> {
+        cycle::enter(self, name)
+    }
+
+    // 在初始化锁内再次检查get，仍未初始化时调用init并由publish保存结果
+    // init返回错误时不调用publish并记录错误信息，之后的调用可以重试
+    // 初始化闭包中再次进入时返回AccessError::Cycle，而不是在初始化锁上死锁
+    pub(crate) fn run<T, V, E: Display>(
+        &self,
+        name: &'static str,
+        get: impl Fn() -> Option<T>,
+        init: impl FnOnce() -> Result<V, E>,
+        publish: impl FnOnce(V) -> T,
+    ) -> Result<T, TryInitError<E>> {
+        if let Some(value) = get() {
+            return Ok(value);
+        }
+        let _init = self.enter(name)?;
+        let mut last_error = self
+            .last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = get() {
+            return Ok(value);
+        }
+        match init() {
+            Ok(value) => Ok(publish(value)),
+            Err(err) => {
+                *last_error = Some(err.to_string());
+                Err(TryInitError::Init(err))
+            }
+        }
+    }
+
+    // 最近一次初始化失败的错误信息
+    pub(crate) fn last_error(&self) -> Option<String> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
@@ -0,0 +1,96 @@
+// 方案3和方案5的可失败初始化
+// 两者的实例是进程级的，单元测试中其他测试可能已经初始化了它们，
+// 这里在独立的测试进程中按固定顺序测试: 失败后重试、直接和间接的初始化环、关闭之后的访问
+// 所有步骤都在同一个测试函数中，保证执行顺序
+use singleton::{AccessError, Singleton3, Singleton5, TryInitError, shutdown};
+
+fn cycle(chain: &[&'static str]) -> TryInitError<String> {
+    TryInitError::Access(AccessError::Cycle(chain.to_vec()))
+}
+
+#[test]
+fn test_try_get_instance() {
+    // 初始化闭包直接访问自己: 内层调用返回环错误，外层初始化失败后保持未初始化
+    let outer = Singleton3::try_get_instance(|| {
+        let inner = Singleton3::try_get_instance(|| Ok::<_, String>("inner".to_string()));
+        assert_eq!(inner.err(), Some(cycle(&["Singleton3", "Singleton3"])));
+        assert_eq!(
+            Singleton3::try_get().err(),
+            Some(AccessError::Cycle(vec!["Singleton3", "Singleton3"]))
+        );
+        Err("direct cycle".to_string())
+    });
+    assert_eq!(
+        outer.err(),
+        Some(TryInitError::Init("direct cycle".to_string()))
+    );
+    assert_eq!(Singleton3::last_error().as_deref(), Some("direct cycle"));
+
+    let outer = Singleton5::try_get_instance(|| {
+        let inner = Singleton5::try_get_instance(|| Ok::<_, String>("inner".to_string()));
+        assert_eq!(inner.err(), Some(cycle(&["Singleton5", "Singleton5"])));
+        assert!(matches!(Singleton5::try_get(), Err(AccessError::Cycle(_))));
+        Err("direct cycle".to_string())
+    });
+    assert_eq!(
+        outer.err(),
+        Some(TryInitError::Init("direct cycle".to_string()))
+    );
+    assert_eq!(Singleton5::last_error().as_deref(), Some("direct cycle"));
+
+    // 经由其他单例间接访问自己，依赖链列出经过的所有单例
+    let outer = Singleton3::try_get_instance(|| {
+        Singleton5::try_get_instance(|| {
+            let inner = Singleton3::try_get_instance(|| Ok::<_, String>("inner".to_string()));
+            assert_eq!(
+                inner.err(),
+                Some(cycle(&["Singleton3", "Singleton5", "Singleton3"]))
+            );
+            Err("indirect cycle".to_string())
+        })
+        .map(|_| "unreachable".to_string())
+        .map_err(|err| err.to_string())
+    });
+    assert_eq!(
+        outer.err(),
+        Some(TryInitError::Init(
+            "initialization failed: indirect cycle".to_string()
+        ))
+    );
+
+    let outer = Singleton5::try_get_instance(|| {
+        Singleton3::try_get_instance(|| {
+            let inner = Singleton5::try_get_instance(|| Ok::<_, String>("inner".to_string()));
+            assert_eq!(
+                inner.err(),
+                Some(cycle(&["Singleton5", "Singleton3", "Singleton5"]))
+            );
+            Err("indirect cycle".to_string())
+        })
+        .map(|_| "unreachable".to_string())
+        .map_err(|err| err.to_string())
+    });
+    assert!(matches!(outer, Err(TryInitError::Init(_))));
+
+    // 环被打断后重试成功，之后不再执行初始化闭包
+    let instance3 = Singleton3::try_get_instance(|| Ok::<_, String>("loaded".to_string())).unwrap();
+    assert_eq!(instance3.get_data(), "loaded");
+    assert!(std::ptr::eq(instance3, Singleton3::get_instance()));
+    assert_eq!(Singleton3::last_error().as_deref(), Some("indirect cycle"));
+
+    let instance5 = Singleton5::try_get_instance(|| Ok::<_, String>("loaded".to_string())).unwrap();
+    assert_eq!(Singleton5::read().get_data(), "loaded");
+    assert_eq!(instance5, Singleton5::get_instance());
+    assert_eq!(instance5.read().get_data(), "loaded");
+
+    // 关闭之后方案5拒绝访问
+    assert!(shutdown().contains(&"Singleton5"));
+    assert_eq!(
+        Singleton5::try_get_instance(|| Ok::<_, String>("unused".to_string())).err(),
+        Some(TryInitError::Access(AccessError::ShutDown("Singleton5")))
+    );
+    assert_eq!(
+        Singleton5::try_get().err(),
+        Some(AccessError::ShutDown("Singleton5"))
+    );
+}